@@ -0,0 +1,262 @@
+use std::collections::TreeMap;
+use serialize::json;
+use serialize::json::{Json, ToJson};
+
+use blendish::widget::*;
+use blendish::menu::option_label;
+use oui::*;
+use oui::geom::Rect;
+
+/// what a widget is, to assistive tech
+#[deriving(PartialEq, Show)]
+pub enum Role {
+    ROLE_LABEL,
+    ROLE_BUTTON,
+    ROLE_CHECKBOX,
+    ROLE_RADIO,
+    ROLE_SLIDER,
+    ROLE_TEXT_FIELD,
+    ROLE_NUMBER_FIELD,
+    ROLE_CHOICE,
+    ROLE_MENU,
+    ROLE_MENU_ITEM,
+    ROLE_SEPARATOR,
+    ROLE_MENU_BAR,
+    ROLE_SCROLLBAR,
+    ROLE_SCROLL_PANEL,
+    ROLE_NODE_EDITOR,
+    ROLE_CANVAS,
+    ROLE_ROW,
+    ROLE_COLUMN,
+    ROLE_PANEL,
+    ROLE_CUSTOM,
+}
+
+impl Role {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ROLE_LABEL => "label",
+            ROLE_BUTTON => "button",
+            ROLE_CHECKBOX => "checkbox",
+            ROLE_RADIO => "radio",
+            ROLE_SLIDER => "slider",
+            ROLE_TEXT_FIELD => "textfield",
+            ROLE_NUMBER_FIELD => "numberfield",
+            ROLE_CHOICE => "choice",
+            ROLE_MENU => "menu",
+            ROLE_MENU_ITEM => "menuitem",
+            ROLE_SEPARATOR => "separator",
+            ROLE_MENU_BAR => "menubar",
+            ROLE_SCROLLBAR => "scrollbar",
+            ROLE_SCROLL_PANEL => "scrollpanel",
+            ROLE_NODE_EDITOR => "nodeeditor",
+            ROLE_CANVAS => "canvas",
+            ROLE_ROW => "row",
+            ROLE_COLUMN => "column",
+            ROLE_PANEL => "panel",
+            ROLE_CUSTOM => "custom",
+        }
+    }
+}
+
+/// the value a widget edits
+#[deriving(PartialEq, Show)]
+pub enum AccessValue {
+    // a check's option
+    Checked(bool),
+    // the group's selected child id, and whether that's this radio
+    Selected { pub index: i32, pub selected: bool },
+    // a slider's progress, or how far a scrollbar or panel is scrolled, 0..1
+    Progress(f32),
+    // a text field's text, as being edited
+    Text(String),
+    // a number field's value
+    Number(f32),
+    // the option a choice is on
+    Chosen(i32),
+}
+
+/// one node of the accessibility tree; children in item order
+pub struct AccessNode {
+    pub item: Item,
+    pub tag: Tag,
+    pub role: Role,
+    // the widget's text; empty for containers
+    pub name: String,
+    pub value: Option<AccessValue>,
+    pub state: ItemState,
+    // in root coordinates
+    pub bounds: Rect,
+    pub children: Vec<AccessNode>,
+}
+
+impl AccessNode {
+    /// depth-first search for the first node named 'name'
+    pub fn find(&self, name: &str) -> Option<&AccessNode> {
+        if self.name.as_slice() == name { return Some(self); }
+        for kid in self.children.iter() {
+            match kid.find(name) {
+                Some(node) => return Some(node),
+                None => {}
+            }
+        }
+        None
+    }
+
+    /// depth-first search for the first node tagged 'tag'
+    pub fn find_tag(&self, tag: Tag) -> Option<&AccessNode> {
+        if self.tag == tag { return Some(self); }
+        for kid in self.children.iter() {
+            match kid.find_tag(tag) {
+                Some(node) => return Some(node),
+                None => {}
+            }
+        }
+        None
+    }
+
+    /// the tree as pretty-printed JSON
+    pub fn to_json_string(&self) -> String {
+        self.to_json().to_pretty_str()
+    }
+}
+
+/// describe 'item' and everything below it; call after layout(), so the
+/// bounds are current
+pub fn access_tree(ui: &mut Context<Widget>, item: Item) -> AccessNode {
+    let parent = ui.parent(item);
+    let frozen = parent.valid() && ui.is_frozen(parent);
+    access_subtree(ui, item, frozen)
+}
+
+// 'frozen' if a container above item is, which freezes it, too
+fn access_subtree(ui: &mut Context<Widget>, item: Item, frozen: bool) -> AccessNode {
+    let kidid = ui.get_child_id(item);
+    let (role, name, value) = match *ui.get_widget(item) {
+        Label { iconid:_, text:ref label } =>
+            (ROLE_LABEL, label.clone(), None),
+        Button { iconid:_, text:ref label } =>
+            (ROLE_BUTTON, label.clone(), None),
+        Check { text:ref label, option:ref option } =>
+            (ROLE_CHECKBOX, label.clone(), Some(Checked(option.get()))),
+        Radio { iconid:_, text:ref label, index:ref index } =>
+            (ROLE_RADIO, label.clone(), Some(Selected {
+                index: index.get(), selected: index.get() == kidid
+            })),
+        Slider { text:ref label, progress:ref progress } =>
+            (ROLE_SLIDER, label.clone(), Some(Progress(progress.get()))),
+        TextField { iconid:_, text:ref text, edit:ref edit, carets:_ } => {
+            let shown = match *edit { Some(ref e) => e.text.clone(), None => text.get() };
+            (ROLE_TEXT_FIELD, String::new(), Some(Text(shown)))
+        }
+        NumberField { text:ref label, value:ref value, spec:_, start:_, last_click:_,
+            edit:_, carets:_ } =>
+            (ROLE_NUMBER_FIELD, label.clone(), Some(Number(value.get()))),
+        Choice { options:ref options, index:ref index, menu:_ } => {
+            let (_, label) = option_label(options.as_slice(), index.get());
+            (ROLE_CHOICE, label.to_string(), Some(Chosen(index.get())))
+        }
+        Menu { owner:_, highlight:_ } => (ROLE_MENU, String::new(), None),
+        MenuItem { iconid:_, text:ref label, index:_, shortcut:_, check:ref check,
+            submenu:_, handler:_ } =>
+            (ROLE_MENU_ITEM, label.clone(), check.as_ref().map(|o| Checked(o.get()))),
+        MenuLabel { iconid:_, text:ref label } => (ROLE_LABEL, label.clone(), None),
+        MenuSeparator { unused:_ } => (ROLE_SEPARATOR, String::new(), None),
+        MenuBar { unused:_ } => (ROLE_MENU_BAR, String::new(), None),
+        MenuTitle { text:ref label, menu:_ } => (ROLE_MENU_ITEM, label.clone(), None),
+        ScrollBar { offset:ref offset, size:_, panel:_, grab:_ } =>
+            (ROLE_SCROLLBAR, String::new(), Some(Progress(offset.get()))),
+        ScrollPanel { offset:ref offset } =>
+            (ROLE_SCROLL_PANEL, String::new(), Some(Progress(offset.get()))),
+        NodeEditor { graph:_, selected:_, edit:_, start:_ } =>
+            (ROLE_NODE_EDITOR, String::new(), None),
+        Canvas { unused:_ } => (ROLE_CANVAS, String::new(), None),
+        Row { unused:_ } => (ROLE_ROW, String::new(), None),
+        Column { unused:_ } => (ROLE_COLUMN, String::new(), None),
+        Panel { unused:_ } => (ROLE_PANEL, String::new(), None),
+        Custom { control:ref control } => (ROLE_CUSTOM, control.label(), None),
+    };
+
+    let state = if frozen { FROZEN } else { ui.get_state(item) };
+    let mut children = Vec::new();
+    let mut kid = ui.first_child(item);
+    while kid.valid() {
+        children.push(access_subtree(ui, kid, state == FROZEN));
+        kid = ui.next_sibling(kid);
+    }
+
+    AccessNode {
+        item: item,
+        tag: ui.get_tag(item),
+        role: role,
+        name: name,
+        value: value,
+        state: state,
+        bounds: ui.get_abs_rect(item),
+        children: children,
+    }
+}
+
+/// the whole ui as JSON, for a headless test harness
+pub fn export_json(ui: &mut Context<Widget>) -> String {
+    let root = ui.root();
+    access_tree(ui, root).to_json_string()
+}
+
+fn state_name(state: ItemState) -> &'static str {
+    match state {
+        COLD => "cold",
+        HOT => "hot",
+        ACTIVE => "active",
+        FROZEN => "frozen",
+    }
+}
+
+impl ToJson for AccessValue {
+    fn to_json(&self) -> Json {
+        let mut obj = TreeMap::new();
+        match *self {
+            Checked(on) => {
+                obj.insert("checked".to_string(), on.to_json());
+            }
+            Selected { index, selected } => {
+                obj.insert("index".to_string(), index.to_json());
+                obj.insert("selected".to_string(), selected.to_json());
+            }
+            Progress(p) => {
+                obj.insert("progress".to_string(), p.to_json());
+            }
+            Text(ref text) => {
+                obj.insert("text".to_string(), text.to_json());
+            }
+            Number(v) => {
+                obj.insert("number".to_string(), v.to_json());
+            }
+            Chosen(index) => {
+                obj.insert("chosen".to_string(), index.to_json());
+            }
+        }
+        json::Object(obj)
+    }
+}
+
+impl ToJson for AccessNode {
+    fn to_json(&self) -> Json {
+        let mut bounds = TreeMap::new();
+        bounds.insert("x".to_string(), self.bounds.x.to_json());
+        bounds.insert("y".to_string(), self.bounds.y.to_json());
+        bounds.insert("w".to_string(), self.bounds.w.to_json());
+        bounds.insert("h".to_string(), self.bounds.h.to_json());
+
+        let mut obj = TreeMap::new();
+        obj.insert("item".to_string(), self.item.itemid.to_json());
+        obj.insert("tag".to_string(), self.tag.to_json());
+        obj.insert("role".to_string(), self.role.name().to_string().to_json());
+        obj.insert("name".to_string(), self.name.to_json());
+        obj.insert("value".to_string(), self.value.to_json());
+        obj.insert("state".to_string(), state_name(self.state).to_string().to_json());
+        obj.insert("bounds".to_string(), json::Object(bounds));
+        obj.insert("children".to_string(), self.children.to_json());
+        json::Object(obj)
+    }
+}
@@ -0,0 +1,169 @@
+use blendish::*;
+use blendish::themed_draw::ThemedDraw;
+use blendish::widget::*;
+use oui::*;
+
+/// an infinite canvas: append items to it, placed with set_absolute() in
+/// canvas points. the wheel zooms it around the cursor, dragging with the
+/// middle button pans it; see Context::set_canvas, and fit_canvas() to
+/// bring everything into view.
+pub fn canvas(ui: &mut Context<Widget>, parent: Item, tag: Tag) -> Item {
+    let item = ui.item(Canvas { unused: 0 });
+    ui.set_tag(item, tag);
+    ui.set_canvas(item, true);
+    ui.append(parent, item);
+    return item;
+}
+
+/// the distance between grid lines at 'zoom', in pixels: CANVAS_GRID_SPACING
+/// points, doubled as often as it takes for the lines not to crowd
+pub fn grid_step(zoom: f32) -> f32 {
+    let mut step = CANVAS_GRID_SPACING * zoom;
+    while step < CANVAS_GRID_MIN_SPACING {
+        step *= 2.0;
+    }
+    step
+}
+
+/// draw a canvas item's backdrop at (x, y, w, h): its grid, moving along
+/// with the view
+pub fn draw_canvas(vg: &mut ThemedContext, x: f32, y: f32, w: f32, h: f32,
+    view: &CanvasView)
+{
+    vg.draw_canvas_grid(x, y, w, h, x + view.pan.x, y + view.pan.y, grid_step(view.zoom));
+}
+
+#[test]
+fn test_grid_thins_out() {
+    assert_eq!(grid_step(1.0), CANVAS_GRID_SPACING);
+    assert_eq!(grid_step(2.0), 2.0 * CANVAS_GRID_SPACING);
+    // at 0.1, 2 pixels apart: doubled twice, to 8
+    assert!(grid_step(0.1) >= CANVAS_GRID_MIN_SPACING);
+    assert!(grid_step(0.1) < 2.0 * CANVAS_GRID_MIN_SPACING);
+}
+
+#[cfg(test)]
+fn test_ui(ui: &mut Context<Widget>) -> Item {
+    let root = ui.item(Panel { unused: 0 });
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 400, 300);
+    let item = canvas(ui, root, 1);
+    ui.set_layout(item, FILL);
+    item
+}
+
+#[test]
+fn test_hit_through_the_view() {
+    use blendish::driver::Driver;
+    use oui::geom::Vec2;
+
+    let mut ui: Context<Widget> = Context::create_context();
+    let item = test_ui(&mut ui);
+    let ok = button(&mut ui, item, 2, -1, "OK", None);
+    ui.set_size(ok, 80, WIDGET_HEIGHT);
+    ui.set_absolute(ok, 100, 100);
+    ui.layout();
+    let mut view = CanvasView::new();
+    view.zoom = 2.0;
+    view.pan = Vec2 { x: -100.0, y: -100.0 };
+    ui.set_canvas_view(item, view);
+
+    // canvas point (100, 100) shows at (100, 100), and the button twice
+    // as large
+    let mut driver = Driver::new(&mut ui);
+    driver.move_to(90.0, 90.0);
+    driver.assert_state(ok, COLD);
+    driver.move_to(250.0, 120.0);
+    driver.assert_state(ok, HOT);
+}
+
+#[test]
+fn test_wheel_zooms_around_the_cursor() {
+    use blendish::driver::Driver;
+    use oui::geom::Vec2;
+    use oui::canvas::ZOOM_STEP;
+
+    let mut ui: Context<Widget> = Context::create_context();
+    let item = test_ui(&mut ui);
+    ui.layout();
+    ui.set_zoom_limits(item, 0.5, 2.0);
+
+    let mut driver = Driver::new(&mut ui);
+    driver.wheel(200.0, 150.0, 1.0);
+    let view = driver.ui.get_canvas_view(item).unwrap();
+    assert_eq!(view.zoom, ZOOM_STEP);
+    let p = view.to_canvas(Vec2 { x: 200.0, y: 150.0 });
+    assert!((p.x - 200.0).abs() < 0.001 && (p.y - 150.0).abs() < 0.001);
+    // no further than the limit
+    driver.wheel(200.0, 150.0, 10.0);
+    assert_eq!(driver.ui.get_canvas_view(item).unwrap().zoom, 2.0);
+}
+
+#[test]
+fn test_middle_drag_pans() {
+    use blendish::driver::Driver;
+    use oui::geom::Vec2;
+
+    let mut ui: Context<Widget> = Context::create_context();
+    let item = test_ui(&mut ui);
+    ui.layout();
+
+    let mut driver = Driver::new(&mut ui);
+    driver.middle_drag(200.0, 150.0, 30.0, -20.0);
+    assert_eq!(driver.ui.get_canvas_view(item).unwrap().pan, Vec2 { x: 30.0, y: -20.0 });
+    // the button is up: moving on doesn't pan
+    driver.move_to(0.0, 0.0);
+    assert_eq!(driver.ui.get_canvas_view(item).unwrap().pan, Vec2 { x: 30.0, y: -20.0 });
+}
+
+#[test]
+fn test_fit_to_content() {
+    use oui::geom::Vec2;
+
+    let mut ui: Context<Widget> = Context::create_context();
+    let item = test_ui(&mut ui);
+    let a = button(&mut ui, item, 2, -1, "A", None);
+    ui.set_size(a, 80, 20);
+    ui.set_absolute(a, -200, 0);
+    let b = button(&mut ui, item, 3, -1, "B", None);
+    ui.set_size(b, 80, 20);
+    ui.set_absolute(b, 300, 400);
+    ui.layout();
+    ui.fit_canvas(item, 10.0);
+
+    // 580 x 420 points in 380 x 280 pixels: the width decides
+    let view = ui.get_canvas_view(item).unwrap();
+    assert_eq!(view.zoom, 380.0 / 580.0);
+    let center = view.to_view(Vec2 { x: 90.0, y: 210.0 });
+    assert!((center.x - 200.0).abs() < 0.001 && (center.y - 150.0).abs() < 0.001);
+}
+
+#[test]
+fn test_drag_a_node_on_a_zoomed_canvas() {
+    use blendish::driver::Driver;
+    use blendish::node::{Node, NodeGraph, node_editor};
+
+    let mut g = NodeGraph::new();
+    g.add_node(Node::new("Texture", 10.0, 10.0, 120.0).output("Color"));
+    let graph = Property::new(g);
+    let mut ui: Context<Widget> = Context::create_context();
+    let item = test_ui(&mut ui);
+    let editor = node_editor(&mut ui, item, 2, graph.clone());
+    ui.set_size(editor, 2000, 2000);
+    ui.set_absolute(editor, 0, 0);
+    ui.layout();
+    let mut view = CanvasView::new();
+    view.zoom = 2.0;
+    ui.set_canvas_view(item, view);
+
+    // by its title bar, at (60, 20) in the canvas; 60 x 100 pixels is
+    // 30 x 50 points
+    let mut driver = Driver::new(&mut ui);
+    driver.move_to(120.0, 40.0);
+    driver.press();
+    driver.move_to(150.0, 90.0);
+    driver.move_to(180.0, 140.0);
+    driver.release();
+    let node = graph.get().nodes[0].clone();
+    assert_eq!((node.x, node.y), (40.0, 60.0));
+}
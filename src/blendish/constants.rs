@@ -49,6 +49,12 @@ pub static HOVER_SHADE: i32 = 15;			// percent, -100..100
 
 /// size of number field arrow
 pub static NUMBER_ARROW_SIZE: f32 = 4.0;
+/// width of the clickable arrow area at either end of a number field
+pub static NUMBER_ARROW_ZONE: f32 = 16.0;
+/// distance the cursor moves before a press on a number field is a drag
+pub static NUMBER_DRAG_THRESHOLD: f32 = 3.0;
+/// longest time between the clicks of a double-click, in seconds
+pub static DOUBLE_CLICK_TIME: f64 = 0.3;
 
 /// default text color
 /// TODO fix Color so that it can be struct-initialized
@@ -80,6 +86,50 @@ pub static MENU_RADIUS: f32 = 3.0;
 pub static SHADOW_FEATHER: f32 = 12.0;
 /// alpha of menu popup shadow
 pub static SHADOW_ALPHA: f32 = 0.5;
+/// height of a menu separator
+pub static MENU_SEPARATOR_HEIGHT: u32 = 7;
+/// horizontal inset of a menu separator's line
+pub static MENU_SEPARATOR_INSET: f32 = 4.0;
+/// alpha of a menu separator's line, relative to the menu text
+pub static MENU_SEPARATOR_ALPHA: f32 = 0.25;
+/// least width of a popup menu
+pub static MENU_WIDTH: u32 = 160;
+/// width of a menu bar title that hasn't been measured
+pub static MENUBAR_ITEM_WIDTH: u32 = 48;
+/// how long the cursor rests on a menu item before its submenu opens, in seconds
+pub static SUBMENU_DELAY: f64 = 0.25;
+/// how far below the cursor a tooltip appears
+pub static TOOLTIP_OFFSET: f32 = 20.0;
+/// least space between a tooltip's text and its shortcut
+pub static TOOLTIP_SHORTCUT_GAP: f32 = 16.0;
+
+
+/// radius of a node's sockets
+pub static NODE_PORT_RADIUS: f32 = 5.0;
+/// how near the cursor has to be to a socket to grab it
+pub static NODE_PORT_HIT_RADIUS: f32 = 8.0;
+/// top margin of node content
+pub static NODE_MARGIN_TOP: f32 = 25.0;
+/// bottom margin of node content
+pub static NODE_MARGIN_DOWN: f32 = 5.0;
+/// left and right margin of node content
+pub static NODE_MARGIN_SIDE: f32 = 10.0;
+/// height of node title bar
+pub static NODE_TITLE_HEIGHT: f32 = 20.0;
+/// width of node title arrow click area
+pub static NODE_ARROW_AREA_WIDTH: f32 = 20.0;
+/// outline width of a wire between sockets
+pub static NODE_WIRE_OUTLINE_WIDTH: f32 = 4.0;
+/// width of a wire between sockets
+pub static NODE_WIRE_WIDTH: f32 = 2.0;
+/// radius of a node box
+pub static NODE_RADIUS: f32 = 8.0;
+/// distance between canvas grid lines, in canvas points
+pub static CANVAS_GRID_SPACING: f32 = 20.0;
+/// grid lines closer than this (pixels) are thinned out
+pub static CANVAS_GRID_MIN_SPACING: f32 = 8.0;
+/// shade of the grid lines against the background
+pub static CANVAS_GRID_SHADE: i32 = -10;
 
 
 /// max glyphs for position testing
@@ -0,0 +1,194 @@
+use std::mem::replace;
+
+use blendish::*;
+use blendish::widget::*;
+use blendish::measure::FontMetrics;
+use oui::*;
+use oui::geom::{Vec2, Rect};
+
+/// what a control is told about an event
+pub struct EventInfo {
+    pub event: EventFlags,
+    // the control's rect, in root coordinates
+    pub rect: Rect,
+    pub cursor: Vec2,
+    // cursor movement since the button went down
+    pub cursor_start_delta: Vec2,
+    pub time: f64,
+}
+
+/// a widget defined outside this crate. wrap it with control() to put it
+/// in a Context<Widget>, next to the built-in widgets.
+pub trait Control {
+    /// draw into 'rect' (root coordinates); a frozen control is drawn COLD,
+    /// and dimmed by draw_ui
+    fn draw(&self, vg: &mut ThemedContext, rect: Rect, state: ItemState);
+
+    /// preferred size, for text in 'metrics'; 0 in a dimension leaves it
+    /// to the layout
+    fn measure(&self, _metrics: &FontMetrics) -> Vec2 {
+        Vec2 { x: 0.0, y: WIDGET_HEIGHT as f32 }
+    }
+
+    /// the events handle_event wants to see
+    fn events(&self) -> EventFlags { BUTTON0_DOWN }
+
+    /// 'ui' is the context the control's 'item' is in: set properties,
+    /// push undo steps, take the focus or open menus through it. returns
+    /// true if the control has to be redrawn
+    fn handle_event(&mut self, _ui: &mut Context<Widget>, _item: Item,
+        _info: &EventInfo) -> bool { false }
+
+    /// name, for accessibility and tests
+    fn label(&self) -> String { String::new() }
+}
+
+/// add a custom control to 'parent'
+pub fn control(ui: &mut Context<Widget>, parent: Item, tag: Tag,
+    control: Box<Control+'static>)
+-> Item
+{
+    let events = control.events();
+    let item = ui.item(Custom { control: control });
+    ui.set_tag(item, tag);
+    // a widget's height, until the measure pass asks the control
+    ui.set_preferred_size(item, 0, WIDGET_HEIGHT);
+    ui.set_handler(item, Some(controlhandler), events);
+    ui.append(parent, item);
+    return item;
+}
+
+/// give a control's item the size its measure() asks for, as its
+/// preferred size, so the layout can still stretch it; returns true if
+/// that changed
+pub fn measure_control(ui: &mut Context<Widget>, item: Item,
+    metrics: &FontMetrics) -> bool
+{
+    let size = match *ui.get_widget(item) {
+        Custom { control:ref control } => control.measure(metrics),
+        _ => return false
+    };
+    let (w, h) = (size.x.ceil(), size.y.ceil());
+    if ui.get_preferred_size(item) == (Vec2 { x: w, y: h }) { return false; }
+    ui.set_preferred_size(item, w as u32, h as u32);
+    true
+}
+
+/// measure the controls in 'item' and below it again; hosts run this
+/// before a layout, as a control's handle_event may have changed what it
+/// shows. returns true if any size changed
+pub fn measure_controls(ui: &mut Context<Widget>, item: Item,
+    metrics: &FontMetrics) -> bool
+{
+    let mut changed = measure_control(ui, item, metrics);
+    let mut kid = ui.first_child(item);
+    while kid.valid() {
+        changed = measure_controls(ui, kid, metrics) || changed;
+        kid = ui.next_sibling(kid);
+    }
+    changed
+}
+
+// stands in for a control while it handles an event
+struct Detached;
+
+impl Control for Detached {
+    fn draw(&self, _vg: &mut ThemedContext, _rect: Rect, _state: ItemState) {}
+}
+
+// passes events on to the control
+pub fn controlhandler(ui: &mut Context<Widget>, item: Item, event: EventFlags) {
+    let info = EventInfo {
+        event: event,
+        rect: ui.get_abs_rect(item),
+        cursor: ui.get_cursor(),
+        cursor_start_delta: ui.get_cursor_start_delta(),
+        time: ui.get_time(),
+    };
+    // taken out of the widget, so that it can have the context
+    let mut control = match *ui.get_widget(item) {
+        Custom { control: ref mut control } =>
+            replace(control, box Detached as Box<Control+'static>),
+        _ => return
+    };
+    let changed = control.handle_event(ui, item, &info);
+    match *ui.get_widget(item) {
+        Custom { control: ref mut slot } => { *slot = control; }
+        _ => {}
+    }
+    if changed {
+        // what it shows may need a different size; the host measures it
+        // again before the layout
+        ui.request_layout();
+        ui.request_redraw();
+    }
+}
+
+#[test]
+fn test_control_resizes_with_its_content() {
+    use blendish::driver::Driver;
+    use blendish::measure::{FixedMetrics, measure};
+
+    // its text, one click longer with each click
+    struct Growing { clicks: uint }
+    impl Control for Growing {
+        fn draw(&self, _vg: &mut ThemedContext, _rect: Rect, _state: ItemState) {}
+        fn measure(&self, metrics: &FontMetrics) -> Vec2 {
+            let text = String::from_char(5 + self.clicks, 'x');
+            Vec2 { x: metrics.text_width(text.as_slice()), y: WIDGET_HEIGHT as f32 }
+        }
+        fn handle_event(&mut self, _ui: &mut Context<Widget>, _item: Item,
+            _info: &EventInfo) -> bool
+        {
+            self.clicks += 1;
+            true
+        }
+    }
+
+    let mut ui: Context<Widget> = Context::create_context();
+    let root = ui.item(Panel { unused: 0 });
+    ui.set_layout(root, LEFT|TOP);
+    let item = control(&mut ui, root, 1, box Growing { clicks: 0 });
+    measure(&mut ui, root, &FixedMetrics { advance: 7.0 });
+    ui.layout();
+    assert_eq!(ui.get_rect(item).w, 35.0);
+    assert_eq!(ui.get_rect(item).h, WIDGET_HEIGHT as f32);
+
+    let mut driver = Driver::new(&mut ui);
+    // measured again before the driver lays out
+    driver.click(item);
+    assert_eq!(driver.ui.get_rect(item).w, 42.0);
+}
+
+#[test]
+fn test_control_takes_the_focus() {
+    use blendish::driver::Driver;
+
+    struct Focusing;
+    impl Control for Focusing {
+        fn draw(&self, _vg: &mut ThemedContext, _rect: Rect, _state: ItemState) {}
+        fn handle_event(&mut self, ui: &mut Context<Widget>, item: Item,
+            _info: &EventInfo) -> bool
+        {
+            ui.set_focus(item);
+            false
+        }
+        fn label(&self) -> String { "focusing".to_string() }
+    }
+
+    let mut ui: Context<Widget> = Context::create_context();
+    let root = ui.item(Panel { unused: 0 });
+    ui.set_layout(root, LEFT|TOP);
+    let item = control(&mut ui, root, 1, box Focusing);
+    ui.set_size(item, 80, WIDGET_HEIGHT);
+    ui.layout();
+
+    let mut driver = Driver::new(&mut ui);
+    driver.click(item);
+    assert!(driver.ui.get_focus() == item);
+    // and it's back in its widget, to be drawn
+    match *driver.ui.get_widget(item) {
+        Custom { control:ref control } => assert_eq!(control.label().as_slice(), "focusing"),
+        _ => fail!()
+    }
+}
@@ -0,0 +1,202 @@
+use blendish::widget::*;
+use blendish::accessibility::access_tree;
+use blendish::control::measure_controls;
+use blendish::measure::FixedMetrics;
+use oui::*;
+use oui::geom::Vec2;
+
+// simulated seconds per frame
+static FRAME_TIME: f64 = 1.0 / 60.0;
+
+/// how a test names an item: by its widget's text, its tag, or the item
+pub trait Locator {
+    fn locate(&self, ui: &mut Context<Widget>) -> Option<Item>;
+}
+
+impl<'a> Locator for &'a str {
+    fn locate(&self, ui: &mut Context<Widget>) -> Option<Item> {
+        let root = ui.root();
+        access_tree(ui, root).find(*self).map(|node| node.item)
+    }
+}
+
+impl Locator for Item {
+    fn locate(&self, _ui: &mut Context<Widget>) -> Option<Item> {
+        Some(*self)
+    }
+}
+
+impl Locator for Tag {
+    fn locate(&self, ui: &mut Context<Widget>) -> Option<Item> {
+        let root = ui.root();
+        access_tree(ui, root).find_tag(*self).map(|node| node.item)
+    }
+}
+
+/// drives a laid-out ui headless, the way a user would: it moves the
+/// cursor and presses the button, one process() frame at a time, with
+/// simulated time.
+///
+///     let mut driver = Driver::new(&mut ui);
+///     driver.click("Item 7");
+///     driver.assert_state("Item 7", ACTIVE);
+pub struct Driver<'a> {
+    pub ui: &'a mut Context<Widget>,
+    // what custom controls measure their text with
+    pub metrics: FixedMetrics,
+    time: f64,
+}
+
+impl<'a> Driver<'a> {
+    pub fn new(ui: &'a mut Context<Widget>) -> Driver<'a> {
+        let time = ui.get_time();
+        Driver { ui: ui, metrics: FixedMetrics { advance: 7.0 }, time: time }
+    }
+
+    /// the item, or fail the test
+    pub fn find<L: Locator>(&mut self, loc: L) -> Item {
+        match loc.locate(&mut *self.ui) {
+            Some(item) => item,
+            None => fail!("no item matches the locator")
+        }
+    }
+
+    /// run one frame with the current cursor and button, laying out
+    /// again if a property calls for it, as the host would
+    pub fn frame(&mut self) {
+        self.time += FRAME_TIME;
+        self.ui.set_time(self.time);
+        self.ui.process();
+        if self.ui.needs_layout() {
+            let root = self.ui.root();
+            measure_controls(&mut *self.ui, root, &self.metrics);
+            self.ui.layout();
+        }
+    }
+
+    /// run frames until 'seconds' of simulated time have passed
+    pub fn wait(&mut self, seconds: f64) {
+        let end = self.time + seconds;
+        while self.time < end { self.frame(); }
+    }
+
+    pub fn move_to(&mut self, x: f32, y: f32) {
+        self.ui.set_cursor(x, y);
+        self.frame();
+    }
+
+    pub fn press(&mut self) {
+        self.ui.set_button(0, true);
+        self.frame();
+    }
+
+    pub fn release(&mut self) {
+        self.ui.set_button(0, false);
+        self.frame();
+    }
+
+    /// press and release 'key'; it goes to the focused item
+    pub fn key(&mut self, key: Key, mods: KeyMods) {
+        self.ui.set_key(key, mods, true);
+        self.frame();
+        self.ui.set_key(key, mods, false);
+        self.frame();
+    }
+
+    /// type 'text' into the focused item
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.ui.set_char(c);
+        }
+        self.frame();
+    }
+
+    /// center of the item, in root coordinates
+    pub fn center_of<L: Locator>(&mut self, loc: L) -> Vec2 {
+        let item = self.find(loc);
+        let rect = self.ui.get_abs_rect(item);
+        Vec2 { x: rect.x + rect.w*0.5, y: rect.y + rect.h*0.5 }
+    }
+
+    /// hover the item's center, press and release
+    pub fn click<L: Locator>(&mut self, loc: L) {
+        let c = self.center_of(loc);
+        self.move_to(c.x, c.y);
+        self.press();
+        self.release();
+    }
+
+    /// hover the item's center, press and release the right button
+    pub fn context_click<L: Locator>(&mut self, loc: L) {
+        let c = self.center_of(loc);
+        self.move_to(c.x, c.y);
+        self.ui.set_button(1, true);
+        self.frame();
+        self.ui.set_button(1, false);
+        self.frame();
+    }
+
+    /// turn the wheel by 'notches' with the cursor at (x, y)
+    pub fn wheel(&mut self, x: f32, y: f32, notches: f32) {
+        self.ui.set_cursor(x, y);
+        self.ui.set_wheel(0.0, notches);
+        self.frame();
+    }
+
+    /// press the middle button at (x, y), move by (dx, dy) and release
+    pub fn middle_drag(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        self.move_to(x, y);
+        self.ui.set_button(2, true);
+        self.frame();
+        self.move_to(x + dx, y + dy);
+        self.ui.set_button(2, false);
+        self.frame();
+    }
+
+    /// press at the item's center, move by (dx, dy) over a few frames and
+    /// release
+    pub fn drag<L: Locator>(&mut self, loc: L, dx: f32, dy: f32) {
+        let c = self.center_of(loc);
+        self.move_to(c.x, c.y);
+        self.press();
+        let steps = 4u;
+        for i in range(1u, steps + 1) {
+            let t = i as f32 / steps as f32;
+            self.move_to(c.x + dx*t, c.y + dy*t);
+        }
+        self.release();
+    }
+
+    /// drag a slider's handle until its progress is 'value'
+    pub fn drag_slider<L: Locator>(&mut self, loc: L, value: f32) {
+        let item = self.find(loc);
+        let current = match self.progress(item) {
+            Some(p) => p,
+            None => fail!("item {} is not a slider", item.itemid)
+        };
+        let w = self.ui.get_rect(item).w;
+        self.drag(item, (value - current)*w, 0.0);
+    }
+
+    /// the state the item is drawn in
+    pub fn state<L: Locator>(&mut self, loc: L) -> ItemState {
+        let item = self.find(loc);
+        get_widget_state(&mut *self.ui, item)
+    }
+
+    pub fn assert_state<L: Locator>(&mut self, loc: L, state: ItemState) {
+        let item = self.find(loc);
+        let actual = get_widget_state(&mut *self.ui, item);
+        if actual != state {
+            fail!("item {} is {}, expected {}", item.itemid, actual, state);
+        }
+    }
+
+    /// a slider's progress
+    pub fn progress(&mut self, item: Item) -> Option<f32> {
+        match *self.ui.get_widget(item) {
+            Slider { text:_, progress:ref progress } => Some(progress.get()),
+            _ => None
+        }
+    }
+}
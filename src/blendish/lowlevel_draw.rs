@@ -36,6 +36,7 @@ pub trait LowLevelDraw
     fn draw_check        (&mut self,ox:f32,oy:f32, color: Color);
     fn draw_arrow        (&mut self, x:f32, y:f32, s: f32, color: Color);
     fn draw_up_down_arrow(&mut self, x:f32, y:f32, s: f32, color: Color);
+    fn draw_node_arrow_down(&mut self, x:f32, y:f32, s: f32, color: Color);
     fn draw_icon         (&mut self, x:f32, y:f32, icons: &Image, iconid: u32);
     fn draw_icon_label_value(&mut self,
         x:f32,y:f32, w:f32,h:f32,
@@ -56,6 +57,11 @@ pub trait LowLevelDraw
         fontsize: f32,
         label: &str,
         caretcolor: Color, cbegin: uint, cend: uint);
+    fn text_caret_positions(&mut self,
+        iconid: u32,
+        font: &Font,
+        fontsize: f32,
+        label: &str) -> Vec<(uint, f32)>;
 }
 impl LowLevelDraw for Ctx {
 
@@ -385,6 +391,36 @@ impl LowLevelDraw for Ctx {
         self.text_box(x, y, w-TEXT_RADIUS-pleft, label);
     }
 
+    /// Find where draw_icon_label_caret would put a caret in front of each
+    /// character of label, and after the last one: (byte index, x offset
+    /// from the widget's left edge), in order.
+    fn text_caret_positions(&mut self,
+        iconid: u32,
+        font: &Font,
+        fontsize: f32,
+        label: &str
+    ) -> Vec<(uint, f32)> {
+        let mut pleft = TEXT_RADIUS;
+        if iconid != -1 as u32 {
+            pleft += ICON_SHEET_RES as f32;
+        }
+        let mut carets = Vec::new();
+        if label.len() == 0 {
+            carets.push((0u, pleft));
+            return carets;
+        }
+        self.font_face_id(font);
+        self.font_size(fontsize);
+        self.text_align(LEFT|BASELINE);
+        let glyphs = self.text_glyph_positions(pleft, 0.0, label);
+        for g in glyphs.iter() {
+            carets.push((g.byte_index(), g.x()));
+        }
+        let end = if glyphs.len() == 0 { pleft } else { glyphs[glyphs.len()-1].maxx() };
+        carets.push((label.len(), end));
+        carets
+    }
+
     /// Draw a checkmark for an option box with the given upper left coordinates
     /// (ox, oy) with the specified color.
     fn draw_check(&mut self, ox: f32, oy: f32, color: Color)
@@ -430,4 +466,17 @@ impl LowLevelDraw for Ctx {
         self.fill_color(color);
         self.fill();
     }
+
+    /// Draw the down arrow of a node's title bar with its tip at (x, y) and
+    /// size s
+    fn draw_node_arrow_down(&mut self, x: f32, y: f32, s: f32, color: Color)
+    {
+        self.begin_path();
+        self.move_to(x, y);
+        self.line_to(x+0.5*s, y-s);
+        self.line_to(x-0.5*s, y-s);
+        self.close_path();
+        self.fill_color(color);
+        self.fill();
+    }
 }
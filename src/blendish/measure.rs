@@ -0,0 +1,186 @@
+use nanovg::Ctx;
+
+use blendish::*;
+use blendish::widget::*;
+use blendish::control::measure_control;
+use oui::*;
+
+/// how wide text is at LABEL_FONT_SIZE. nanovg does this for real; tests
+/// can do without a font.
+pub trait FontMetrics {
+    fn text_width(&self, text: &str) -> f32;
+}
+
+/// text measured with a nanovg font
+pub struct NanovgMetrics<'a> {
+    ctx: &'a Ctx,
+    font: &'a Font,
+}
+
+impl<'a> NanovgMetrics<'a> {
+    pub fn new(ctx: &'a Ctx, font: &'a Font) -> NanovgMetrics<'a> {
+        NanovgMetrics { ctx: ctx, font: font }
+    }
+}
+
+impl<'a> FontMetrics for NanovgMetrics<'a> {
+    fn text_width(&self, text: &str) -> f32 {
+        self.ctx.font_face_id(self.font);
+        self.ctx.font_size(LABEL_FONT_SIZE);
+        self.ctx.text_advance(1.0, 1.0, text)
+    }
+}
+
+/// every character the same width, for tests
+pub struct FixedMetrics {
+    pub advance: f32,
+}
+
+impl FontMetrics for FixedMetrics {
+    fn text_width(&self, text: &str) -> f32 {
+        self.advance * text.char_len() as f32
+    }
+}
+
+/// the ideal width for a label with the given icon (or none, if < 0) and
+/// text; see label_width
+pub fn text_label_width(metrics: &FontMetrics, iconid: i32, label: &str) -> f32 {
+    let mut w = (PAD_LEFT + PAD_RIGHT) as f32;
+    if iconid >= 0 {
+        w += ICON_SHEET_RES as f32;
+    }
+    if label.len() > 0 {
+        w += metrics.text_width(label);
+    }
+    return w;
+}
+
+/// the width the widget's content asks for, or None for containers, whose
+/// size comes from their children
+pub fn preferred_width(metrics: &FontMetrics, widget: &Widget) -> Option<f32> {
+    let arrows = 2.0 * NUMBER_ARROW_ZONE;
+    let w = match *widget {
+        Label { iconid:iconid, text:ref label } |
+        Button { iconid:iconid, text:ref label } |
+        Radio { iconid:iconid, text:ref label, index:_ } |
+        MenuLabel { iconid:iconid, text:ref label } =>
+            text_label_width(metrics, iconid, label.as_slice()),
+        Check { text:ref label, option:_ } =>
+            text_label_width(metrics, -1, label.as_slice()) + ICON_SHEET_RES as f32,
+        Slider { text:ref label, progress:_ } => {
+            let shown = format!("{}{}100", label, LABEL_SEPARATOR);
+            text_label_width(metrics, -1, shown.as_slice())
+        }
+        TextField { iconid:iconid, text:ref text, edit:_, carets:_ } =>
+            text_label_width(metrics, iconid, text.get().as_slice()),
+        NumberField { text:ref label, value:_, spec:ref spec, start:_, last_click:_,
+            edit:_, carets:_ } => {
+            // as wide as the widest value it takes
+            let widest = max(spec.format(spec.min).len(), spec.format(spec.max).len());
+            let digits = String::from_char(widest, '0');
+            let shown = format!("{}{}{}", label, LABEL_SEPARATOR, digits);
+            text_label_width(metrics, -1, shown.as_slice()) + arrows
+        }
+        Choice { options:ref options, index:_, menu:_ } => {
+            let widest = options.iter().fold(0.0, |w, &(iconid, ref label)| {
+                max(w, text_label_width(metrics, iconid, label.as_slice()))
+            });
+            widest + NUMBER_ARROW_ZONE
+        }
+        MenuItem { iconid:iconid, text:ref label, index:_, shortcut:ref shortcut,
+            check:ref check, submenu:submenu, handler:_ } => {
+            let mut w = text_label_width(metrics, iconid, label.as_slice());
+            if check.is_some() && iconid < 0 {
+                // the check mark takes the icon's place
+                w += ICON_SHEET_RES as f32;
+            }
+            if shortcut.len() > 0 {
+                w += TOOLTIP_SHORTCUT_GAP + metrics.text_width(shortcut.as_slice());
+            }
+            if submenu.valid() {
+                w += NUMBER_ARROW_ZONE;
+            }
+            w
+        }
+        MenuTitle { text:ref label, menu:_ } =>
+            text_label_width(metrics, -1, label.as_slice()),
+        Custom { control:ref control } => {
+            // it measures itself; 0 leaves it to the layout
+            let w = control.measure(metrics).x;
+            if w <= 0.0 { return None; }
+            w
+        }
+        _ => return None
+    };
+    Some(w)
+}
+
+/// the measure pass: give 'item', everything below it, and the menus
+/// they open, the width their text asks for. run it before layout(),
+/// and again when the text or the font changes.
+pub fn measure(ui: &mut Context<Widget>, item: Item, metrics: &FontMetrics) {
+    let (width, popup) = {
+        let widget = ui.get_widget(item);
+        let popup = match *widget {
+            Choice { options:_, index:_, menu:menu } => menu,
+            MenuTitle { text:_, menu:menu } => menu,
+            MenuItem { iconid:_, text:_, index:_, shortcut:_, check:_, submenu:submenu,
+                handler:_ } => submenu,
+            _ => Item::none()
+        };
+        (preferred_width(metrics, &*widget), popup)
+    };
+    match width {
+        Some(w) => ui.set_preferred_size(item, w.ceil() as u32, 0),
+        None => {}
+    }
+    // a control knows its height, too
+    measure_control(ui, item, metrics);
+
+    // menus that aren't open are detached, so they're not among the kids
+    if popup.valid() && ui.parent(popup).invalid() {
+        measure(ui, popup, metrics);
+    }
+    let menu = ui.get_context_menu(item);
+    if menu.valid() && ui.parent(menu).invalid() {
+        measure(ui, menu, metrics);
+    }
+
+    let mut kid = ui.first_child(item);
+    while kid.valid() {
+        measure(ui, kid, metrics);
+        kid = ui.next_sibling(kid);
+    }
+}
+
+#[test]
+fn test_row_fits_its_labels() {
+    let metrics = FixedMetrics { advance: 7.0 };
+    let mut ui: Context<Widget> = Context::create_context();
+    let root = ui.item(Panel { unused: 0 });
+    ui.set_layout(root, LEFT|TOP);
+    let row = row(&mut ui, root);
+    ui.set_layout(row, LEFT|TOP);
+    button(&mut ui, row, 1, -1, "OK", None);
+    button(&mut ui, row, 2, -1, "Cancel", None);
+    measure(&mut ui, root, &metrics);
+    ui.layout();
+
+    // both labels, padded, and the gap between them
+    let pad = (PAD_LEFT + PAD_RIGHT) as f32;
+    assert_eq!(ui.get_rect(row).w, (pad + 14.0) + 8.0 + (pad + 42.0));
+    assert_eq!(ui.get_rect(root).w, ui.get_rect(row).w);
+}
+
+#[test]
+fn test_fixed_size_wins() {
+    let metrics = FixedMetrics { advance: 7.0 };
+    let mut ui: Context<Widget> = Context::create_context();
+    let root = ui.item(Panel { unused: 0 });
+    ui.set_layout(root, LEFT|TOP);
+    let item = label(&mut ui, root, -1, "A rather long label");
+    ui.set_size(item, 50, WIDGET_HEIGHT);
+    measure(&mut ui, root, &metrics);
+    ui.layout();
+    assert_eq!(ui.get_rect(item).w, 50.0);
+}
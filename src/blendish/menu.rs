@@ -0,0 +1,581 @@
+use blendish::*;
+use blendish::widget::*;
+use draw::iconsheet::no_icon;
+use oui::*;
+use oui::geom::Rect;
+
+/// one line of a popup menu
+pub enum MenuEntry {
+    // an option to choose: icon and label
+    MENU_OPTION(i32, String),
+    // a heading over the entries that follow
+    MENU_HEADING(i32, String),
+    MENU_SEPARATOR,
+    // something to do; see MenuCommand
+    MENU_COMMAND(MenuCommand),
+    // icon and label of an entry that opens another menu
+    MENU_SUBMENU(i32, String, Vec<MenuEntry>),
+}
+
+/// a menu entry that runs 'handler' when chosen. the handler is given the
+/// menu item, tagged 'tag', after the menus have closed.
+pub struct MenuCommand {
+    pub tag: Tag,
+    pub iconid: i32,
+    pub label: String,
+    // shown at the right, e.g. "Ctrl+S"; the host handles the keys
+    pub shortcut: String,
+    // toggled when chosen, and shown with a check mark
+    pub check: Option<Property<bool>>,
+    pub enabled: bool,
+    pub handler: Handler<Widget>,
+}
+
+impl MenuCommand {
+    pub fn new(tag: Tag, label: &str, handler: Handler<Widget>) -> MenuCommand {
+        MenuCommand {
+            tag: tag,
+            iconid: no_icon(),
+            label: label.to_string(),
+            shortcut: String::new(),
+            check: None,
+            enabled: true,
+            handler: handler,
+        }
+    }
+
+    pub fn icon(mut self, iconid: i32) -> MenuCommand {
+        self.iconid = iconid;
+        self
+    }
+
+    pub fn shortcut(mut self, shortcut: &str) -> MenuCommand {
+        self.shortcut = shortcut.to_string();
+        self
+    }
+
+    pub fn check(mut self, option: Property<bool>) -> MenuCommand {
+        self.check = Some(option);
+        self
+    }
+
+    pub fn disabled(mut self) -> MenuCommand {
+        self.enabled = false;
+        self
+    }
+}
+
+/// a combo box showing the option 'index' is on; a click opens a popup
+/// menu of 'entries' to choose another. 'index' counts the MENU_OPTIONs.
+pub fn choice(ui: &mut Context<Widget>, parent: Item, tag: Tag,
+    entries: Vec<MenuEntry>, index: Property<i32>)
+-> Item
+{
+    ui.watch(&index);
+    let mut options = Vec::new();
+    for entry in entries.iter() {
+        match *entry {
+            MENU_OPTION(iconid, ref label) => options.push((iconid, label.clone())),
+            _ => {}
+        }
+    }
+    let item = ui.item(Choice { options: options, index: index, menu: Item::none() });
+    ui.set_tag(item, tag);
+    ui.set_size(item, 0, WIDGET_HEIGHT);
+    ui.set_handler(item, Some(choicehandler), BUTTON0_DOWN);
+    ui.append(parent, item);
+
+    let menu = popup_menu(ui, item, entries);
+    match *ui.get_widget(item) {
+        Choice { options:_, index:_, menu:ref mut m } => *m = menu,
+        _ => {}
+    }
+    return item;
+}
+
+/// a bar of menu titles; add them with menubar_menu()
+pub fn menubar(ui: &mut Context<Widget>, parent: Item) -> Item {
+    let item = ui.item(MenuBar { unused: 0 });
+    ui.set_size(item, 0, WIDGET_HEIGHT);
+    ui.set_handler(item, Some(menubarhandler), APPEND);
+    ui.append(parent, item);
+    return item;
+}
+
+/// a title in the menu bar, opening a menu of 'entries'
+pub fn menubar_menu(ui: &mut Context<Widget>, bar: Item, tag: Tag, label: &str,
+    entries: Vec<MenuEntry>)
+-> Item
+{
+    let item = ui.item(MenuTitle { text: label.to_string(), menu: Item::none() });
+    ui.set_tag(item, tag);
+    ui.set_size(item, 0, WIDGET_HEIGHT);
+    // until measure() fits it to its label
+    ui.set_preferred_size(item, MENUBAR_ITEM_WIDTH, 0);
+    ui.set_handler(item, Some(menutitlehandler), BUTTON0_DOWN);
+    ui.append(bar, item);
+
+    let menu = popup_menu(ui, item, entries);
+    match *ui.get_widget(item) {
+        MenuTitle { text:_, menu:ref mut m } => *m = menu,
+        _ => {}
+    }
+    return item;
+}
+
+/// give 'item' a right-click menu of 'entries'
+pub fn context_menu(ui: &mut Context<Widget>, item: Item, entries: Vec<MenuEntry>) -> Item {
+    let menu = popup_menu(ui, item, entries);
+    ui.set_context_menu(item, menu);
+    menu
+}
+
+/// build a popup menu for 'owner', detached until opened
+pub fn popup_menu(ui: &mut Context<Widget>, owner: Item, entries: Vec<MenuEntry>) -> Item {
+    let menu = ui.item(Menu { owner: owner, highlight: -1 });
+    ui.set_handler(menu, Some(menuhandler), APPEND|DISMISS|KEY_DOWN|CONTEXT_MENU);
+    let mut index = 0;
+    for entry in entries.into_iter() {
+        let kid = match entry {
+            MENU_OPTION(iconid, label) => {
+                index += 1;
+                menu_item(ui, MenuItem { iconid: iconid, text: label, index: index - 1,
+                    shortcut: String::new(), check: None, submenu: Item::none(),
+                    handler: None })
+            }
+            MENU_COMMAND(cmd) => {
+                match cmd.check {
+                    Some(ref option) => ui.watch(option),
+                    None => {}
+                }
+                let kid = menu_item(ui, MenuItem { iconid: cmd.iconid, text: cmd.label,
+                    index: -1, shortcut: cmd.shortcut, check: cmd.check,
+                    submenu: Item::none(), handler: cmd.handler });
+                ui.set_tag(kid, cmd.tag);
+                if !cmd.enabled { ui.set_frozen(kid, true); }
+                kid
+            }
+            MENU_SUBMENU(iconid, label, entries) => {
+                let kid = menu_item(ui, MenuItem { iconid: iconid, text: label, index: -1,
+                    shortcut: String::new(), check: None, submenu: Item::none(),
+                    handler: None });
+                let submenu = popup_menu(ui, kid, entries);
+                match *ui.get_widget(kid) {
+                    MenuItem { iconid:_, text:_, index:_, shortcut:_, check:_,
+                        submenu:ref mut s, handler:_ } => *s = submenu,
+                    _ => {}
+                }
+                kid
+            }
+            MENU_HEADING(iconid, label) => {
+                let kid = ui.item(MenuLabel { iconid: iconid, text: label });
+                ui.set_size(kid, 0, WIDGET_HEIGHT);
+                kid
+            }
+            MENU_SEPARATOR => {
+                let kid = ui.item(MenuSeparator { unused: 0 });
+                ui.set_size(kid, 0, MENU_SEPARATOR_HEIGHT);
+                kid
+            }
+        };
+        ui.append(menu, kid);
+    }
+    menu
+}
+
+fn menu_item(ui: &mut Context<Widget>, widget: Widget) -> Item {
+    let item = ui.item(widget);
+    ui.set_size(item, 0, WIDGET_HEIGHT);
+    ui.set_handler(item, Some(menuitemhandler),
+        BUTTON0_DOWN|BUTTON0_HOT_UP|ENTER|LEAVE|TIMER);
+    item
+}
+
+/// show 'menu' under 'anchor' (or above it, if there's no room below), at
+/// least as wide as the anchor and MENU_WIDTH, with the keyboard on the child
+/// 'highlight'. it takes the input until closed. the root's rect is the
+/// screen menus are kept on.
+pub fn open_menu(ui: &mut Context<Widget>, menu: Item, anchor: Item, highlight: i32) {
+    if ui.parent(menu).valid() { return; }
+    let root = ui.root();
+    let at = ui.get_abs_rect(anchor);
+    let w = max(MENU_WIDTH, (at.w / ui.get_scale()) as u32);
+    show_menu(ui, menu, root, at, w, false, highlight);
+    ui.push_modal(menu, true);
+    ui.set_focus(menu);
+}
+
+/// show 'menu' at (x, y), in root coordinates, as for a right-click
+pub fn open_menu_at(ui: &mut Context<Widget>, menu: Item, x: f32, y: f32) {
+    if ui.parent(menu).valid() { return; }
+    let root = ui.root();
+    let at = Rect { x: x, y: y, w: 0.0, h: 0.0 };
+    show_menu(ui, menu, root, at, MENU_WIDTH, false, -1);
+    ui.push_modal(menu, true);
+    ui.set_focus(menu);
+}
+
+// open the submenu of 'item' beside it; from the keyboard, with the
+// keyboard on its first entry
+fn open_submenu(ui: &mut Context<Widget>, item: Item, by_keyboard: bool) {
+    let submenu = submenu_of(ui, item);
+    if submenu.invalid() || ui.parent(submenu).valid() { return; }
+    let menu = ui.parent(item);
+    let at = ui.get_abs_rect(item);
+    let highlight = if by_keyboard { next_entry(ui, submenu, -1, 1) } else { -1 };
+    show_menu(ui, submenu, menu, at, MENU_WIDTH, true, highlight);
+    ui.set_focus(submenu);
+}
+
+// attach 'menu' to 'parent', 'w' (unscaled) wide, placed by 'at' (root
+// coordinates): below it, or beside it for a submenu
+fn show_menu(ui: &mut Context<Widget>, menu: Item, parent: Item, at: Rect, w: u32,
+    beside: bool, highlight: i32)
+{
+    let root = ui.root();
+    let scale = ui.get_scale();
+    let screen = ui.get_abs_rect(root);
+
+    // at least 'w' wide, wider if its entries' text needs it
+    ui.append(parent, menu);
+    ui.set_preferred_size(menu, w, 0);
+    ui.set_absolute(menu, 0, 0);
+    ui.layout();
+
+    let size = ui.get_rect(menu);
+    let (x, y) = place_popup(screen, at, size.w, size.h, beside);
+    let origin = ui.get_abs_rect(parent);
+    ui.set_absolute(menu, ((x - origin.x) / scale) as i32, ((y - origin.y) / scale) as i32);
+    ui.layout();
+
+    match *ui.get_widget(menu) {
+        Menu { owner:_, highlight:ref mut h } => *h = highlight,
+        _ => {}
+    }
+    ui.request_redraw();
+}
+
+// where a popup of w x h goes: below 'at', or above it if it doesn't fit
+// below; beside: right of 'at', or left of it. then moved as far as
+// needed to stay inside 'screen'.
+fn place_popup(screen: Rect, at: Rect, w: f32, h: f32, beside: bool) -> (f32, f32) {
+    let right = screen.x + screen.w;
+    let bottom = screen.y + screen.h;
+    let (mut x, mut y) = if beside { (at.x + at.w, at.y) } else { (at.x, at.y + at.h) };
+    if beside {
+        if x + w > right && at.x - w >= screen.x { x = at.x - w; }
+    } else {
+        if y + h > bottom && at.y - h >= screen.y { y = at.y - h; }
+    }
+    x = max(screen.x, min(x, right - w));
+    y = max(screen.y, min(y, bottom - h));
+    (x, y)
+}
+
+/// put the menu away, with any submenus it has open. the focus goes
+/// back to the menu it's a submenu of; a top menu gives back the input.
+pub fn close_menu(ui: &mut Context<Widget>, menu: Item) {
+    close_submenus(ui, menu, Item::none());
+    let parent = ui.parent(menu);
+    if parent.invalid() { return; }
+    if ui.get_modal() == menu {
+        ui.pop_modal();
+    }
+    if ui.get_focus() == menu {
+        let back = if is_menu(ui, parent) { parent } else { Item::none() };
+        ui.set_focus(back);
+    }
+    ui.detach(menu);
+    ui.request_redraw();
+}
+
+// close every submenu open in 'menu', except 'keep'
+fn close_submenus(ui: &mut Context<Widget>, menu: Item, keep: Item) {
+    let mut open = Vec::new();
+    let mut kid = ui.first_child(menu);
+    while kid.valid() {
+        if kid != keep && is_menu(ui, kid) { open.push(kid); }
+        kid = ui.next_sibling(kid);
+    }
+    for &sub in open.iter() {
+        close_menu(ui, sub);
+    }
+}
+
+// close the menu 'item' is in, and every menu it's a submenu of
+fn close_all(ui: &mut Context<Widget>, item: Item) {
+    let mut top = ui.parent(item);
+    loop {
+        let parent = ui.parent(top);
+        if parent.invalid() || !is_menu(ui, parent) { break; }
+        top = parent;
+    }
+    close_menu(ui, top);
+}
+
+fn is_menu(ui: &mut Context<Widget>, item: Item) -> bool {
+    match *ui.get_widget(item) {
+        Menu { owner:_, highlight:_ } => true,
+        _ => false
+    }
+}
+
+fn submenu_of(ui: &mut Context<Widget>, item: Item) -> Item {
+    match *ui.get_widget(item) {
+        MenuItem { iconid:_, text:_, index:_, shortcut:_, check:_, submenu:submenu, handler:_ } =>
+            submenu,
+        _ => Item::none()
+    }
+}
+
+/// the state to draw a menu-related item in: a choice or menu title with
+/// its menu open is ACTIVE, the menu item the keyboard is on HOT
+pub fn menu_state(ui: &mut Context<Widget>, item: Item, state: ItemState) -> ItemState {
+    if state == FROZEN { return state; }
+    let (is_opener, popup) = match *ui.get_widget(item) {
+        Choice { options:_, index:_, menu:menu } => (true, menu),
+        MenuTitle { text:_, menu:menu } => (true, menu),
+        MenuItem { iconid:_, text:_, index:_, shortcut:_, check:_, submenu:submenu, handler:_ } =>
+            (false, submenu),
+        _ => return state
+    };
+    let open = popup.valid() && ui.parent(popup).valid();
+    if is_opener {
+        return if open { ACTIVE } else { state };
+    }
+    let menu = ui.parent(item);
+    if state != COLD || menu.invalid() { return state; }
+    // an item whose submenu is open stays lit
+    if open { return HOT; }
+    let kidid = ui.get_child_id(item);
+    match *ui.get_widget(menu) {
+        Menu { owner:_, highlight:highlight } if highlight == kidid => HOT,
+        _ => state
+    }
+}
+
+// the choice opens its menu, with the keyboard on the current option
+pub fn choicehandler(ui: &mut Context<Widget>, item: Item, _event: EventFlags) {
+    let (menu, index) = match *ui.get_widget(item) {
+        Choice { options:_, index:ref index, menu:menu } => (menu, index.get()),
+        _ => return
+    };
+    if menu.invalid() { return; }
+    let highlight = option_child(ui, menu, index);
+    open_menu(ui, menu, item, highlight);
+}
+
+pub fn menutitlehandler(ui: &mut Context<Widget>, item: Item, _event: EventFlags) {
+    let menu = match *ui.get_widget(item) {
+        MenuTitle { text:_, menu:menu } => menu,
+        _ => return
+    };
+    if menu.valid() {
+        open_menu(ui, menu, item, -1);
+    }
+}
+
+// titles line up from the left
+pub fn menubarhandler(ui: &mut Context<Widget>, parent: Item, _event: EventFlags) {
+    let item = ui.last_child(parent);
+    let last = ui.prev_sibling(item);
+    ui.set_rel_to_left(item, last);
+    ui.set_layout(item, LEFT|TOP);
+}
+
+// keys move through the entries and choose one, Right and Left open and
+// close submenus; Escape or a click elsewhere closes the menu
+pub fn menuhandler(ui: &mut Context<Widget>, item: Item, event: EventFlags) {
+    match event {
+        APPEND => {
+            let kid = ui.last_child(item);
+            // an open submenu floats beside the entries
+            if is_menu(ui, kid) { return; }
+            let last = ui.prev_sibling(kid);
+            ui.set_rel_to_top(kid, last);
+            ui.set_layout(kid, HFILL|TOP);
+        }
+        DISMISS => close_menu(ui, item),
+        CONTEXT_MENU => {
+            let cursor = ui.get_cursor();
+            open_menu_at(ui, item, cursor.x, cursor.y);
+        }
+        KEY_DOWN => {
+            let highlight = match *ui.get_widget(item) {
+                Menu { owner:_, highlight:highlight } => highlight,
+                _ => return
+            };
+            let count = ui.get_child_count(item);
+            let kid = child_at(ui, item, highlight);
+            let next = match ui.get_key() {
+                Some(KEY_ARROW_DOWN) => next_entry(ui, item, highlight, 1),
+                Some(KEY_ARROW_UP) => next_entry(ui, item, highlight, -1),
+                Some(KEY_HOME) => next_entry(ui, item, -1, 1),
+                Some(KEY_END) => next_entry(ui, item, count, -1),
+                Some(KEY_ENTER) => {
+                    if kid.valid() { activate(ui, kid, true, event); }
+                    return;
+                }
+                Some(KEY_ARROW_RIGHT) => {
+                    if kid.valid() { open_submenu(ui, kid, true); }
+                    return;
+                }
+                Some(KEY_ARROW_LEFT) => {
+                    let parent = ui.parent(item);
+                    if is_menu(ui, parent) { close_menu(ui, item); }
+                    return;
+                }
+                Some(KEY_ESCAPE) => {
+                    close_menu(ui, item);
+                    return;
+                }
+                _ => return
+            };
+            set_highlight(ui, item, next);
+        }
+        _ => {}
+    }
+}
+
+// a click on an entry chooses it or opens its submenu; resting the cursor
+// on one opens its submenu, and closes any other
+pub fn menuitemhandler(ui: &mut Context<Widget>, item: Item, event: EventFlags) {
+    let menu = ui.parent(item);
+    match event {
+        // pressing here mustn't take the keyboard from the menu
+        BUTTON0_DOWN => ui.set_focus(menu),
+        BUTTON0_HOT_UP => activate(ui, item, false, event),
+        ENTER => {
+            let kidid = ui.get_child_id(item);
+            set_highlight(ui, menu, kidid);
+            ui.cancel_timers(item);
+            ui.set_timer(item, SUBMENU_DELAY);
+        }
+        LEAVE => ui.cancel_timers(item),
+        TIMER => {
+            let submenu = submenu_of(ui, item);
+            close_submenus(ui, menu, submenu);
+            if submenu.valid() {
+                open_submenu(ui, item, false);
+            } else {
+                ui.set_focus(menu);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn set_highlight(ui: &mut Context<Widget>, menu: Item, kidid: i32) {
+    match *ui.get_widget(menu) {
+        Menu { owner:_, highlight:ref mut h } => *h = kidid,
+        _ => {}
+    }
+    ui.request_redraw();
+}
+
+// open the entry's submenu, or choose it
+fn activate(ui: &mut Context<Widget>, item: Item, by_keyboard: bool, event: EventFlags) {
+    let submenu = submenu_of(ui, item);
+    if submenu.valid() {
+        let menu = ui.parent(item);
+        close_submenus(ui, menu, submenu);
+        open_submenu(ui, item, by_keyboard);
+    } else {
+        choose(ui, item, event);
+    }
+}
+
+// the entry 'item' was chosen: an option sets the menu's owner to it, a
+// check toggles, each as one undo step. the menus close, then the
+// command's handler runs.
+fn choose(ui: &mut Context<Widget>, item: Item, event: EventFlags) {
+    let (index, check, handler) = match *ui.get_widget(item) {
+        MenuItem { iconid:_, text:_, index:index, shortcut:_, check:ref check,
+            submenu:_, handler:handler } => (index, check.clone(), handler),
+        _ => return
+    };
+    let menu = ui.parent(item);
+    let owner = match *ui.get_widget(menu) {
+        Menu { owner:owner, highlight:_ } => owner,
+        _ => return
+    };
+    let change = if index >= 0 {
+        match *ui.get_widget(owner) {
+            Choice { options:_, index:ref current, menu:_ } => {
+                let before = current.get();
+                if current.set(index) { Some(SetProperty::new(current.clone(), before, index)) }
+                else { None }
+            }
+            _ => None
+        }
+    } else {
+        match check {
+            Some(option) => {
+                let before = option.get();
+                if option.set(!before) { Some(SetProperty::new(option.clone(), before, !before)) }
+                else { None }
+            }
+            None => None
+        }
+    };
+    match change {
+        Some(cmd) => ui.push_command(box cmd),
+        None => {}
+    }
+    close_all(ui, item);
+    match handler {
+        Some(handler) => handler(ui, item, event),
+        None => {}
+    }
+}
+
+// the child of 'menu' at child id 'kidid', or none
+fn child_at(ui: &mut Context<Widget>, menu: Item, kidid: i32) -> Item {
+    let mut kid = ui.first_child(menu);
+    while kid.valid() && ui.get_child_id(kid) != kidid {
+        kid = ui.next_sibling(kid);
+    }
+    kid
+}
+
+// the child id of option 'index', or -1
+fn option_child(ui: &mut Context<Widget>, menu: Item, index: i32) -> i32 {
+    let mut kid = ui.first_child(menu);
+    while kid.valid() {
+        let found = match *ui.get_widget(kid) {
+            MenuItem { iconid:_, text:_, index:i, shortcut:_, check:_, submenu:_, handler:_ } =>
+                i == index,
+            _ => false
+        };
+        if found { return ui.get_child_id(kid); }
+        kid = ui.next_sibling(kid);
+    }
+    -1
+}
+
+// the child id of the next enabled entry from 'from' in direction 'dir',
+// wrapping around; 'from' if there's no other
+fn next_entry(ui: &mut Context<Widget>, menu: Item, from: i32, dir: i32) -> i32 {
+    let count = ui.get_child_count(menu);
+    let mut kidid = from;
+    for _ in range(0, count) {
+        kidid = (kidid + dir + count) % count;
+        let kid = child_at(ui, menu, kidid);
+        let entry = match *ui.get_widget(kid) {
+            MenuItem { iconid:_, text:_, index:_, shortcut:_, check:_, submenu:_, handler:_ } =>
+                true,
+            _ => false
+        };
+        if entry && ui.get_state(kid) != FROZEN { return kidid; }
+    }
+    from
+}
+
+/// icon and label of a choice's option 'index'; no icon and an empty
+/// label if there's no such option
+pub fn option_label(options: &[(i32, String)], index: i32) -> (i32, &str) {
+    match options.get(index as uint) {
+        Some(&(iconid, ref label)) => (iconid, label.as_slice()),
+        None => (no_icon(), "")
+    }
+}
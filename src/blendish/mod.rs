@@ -8,6 +8,7 @@ pub use nanovg::{Image, Font};
 pub use self::constants::*;
 pub use self::theme::ThemedContext;
 pub use self::theme::*;
+pub use self::measure::{FontMetrics, NanovgMetrics, text_label_width};
 pub use util::{min,max,clamp,rgba_f,black,offset_color,};
 
 pub use nanovg::Align as TextAlignment;
@@ -27,7 +28,17 @@ pub mod theme;
 pub mod lowlevel_draw;
 pub mod themed_draw;
 
+pub mod textedit;
 pub mod widget;
+pub mod control;
+pub mod menu;
+pub mod scroll;
+pub mod node;
+pub mod canvas;
+pub mod tooltip;
+pub mod measure;
+pub mod accessibility;
+pub mod driver;
 
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -43,6 +54,7 @@ pub mod widget;
 // pub fn select_corners(radiuses: &mut [f32, ..4], r: f32, flags: CornerFlags)
 // pub fn inner_colors(shade_top: &mut Color, shade_down: &mut Color, theme: &WidgetTheme, state: ItemState, flipActive: bool
 // pub fn text_color(theme: &WidgetTheme, state: ItemState) -> Color
+// pub fn node_wire_color(theme: &NodeTheme, state: ItemState) -> Color
 // pub fn scroll_handle_rect(x: &mut f32, y: &mut f32, w: &mut f32, h: &mut f32, offset: f32, size: f32
 
 
@@ -50,16 +62,7 @@ pub mod widget;
 // returns the ideal width for a label with given icon and text
 pub fn label_width(ctx: &nanovg::Ctx, iconid: i32, label: &str, font: &Font) -> f32
 {
-    let mut w = (PAD_LEFT + PAD_RIGHT) as f32;
-    if iconid >= 0 {
-        w += ICON_SHEET_RES as f32;
-    }
-    if label.len() > 0 {
-        ctx.font_face_id( font);
-        ctx.font_size(LABEL_FONT_SIZE);
-        w += ctx.text_advance(1.0, 1.0, label);
-    }
-    return w;
+    text_label_width(&NanovgMetrics::new(ctx, font), iconid, label)
 }
 
 
@@ -116,6 +119,17 @@ pub fn text_color(theme: &WidgetTheme, state: ItemState) -> Color
     return if state == ACTIVE {theme.textSelectedColor} else {theme.textColor};
 }
 
+// computes the color of a wire's end from a node theme and the state of the
+// node it's attached to.
+pub fn node_wire_color(theme: &NodeTheme, state: ItemState) -> Color
+{
+    match state {
+        HOT => theme.wireSelectColor,
+        ACTIVE => theme.activeNodeColor,
+        _ => rgba_f(0.5, 0.5, 0.5, 1.0)
+    }
+}
+
 
 // computes the bounds of the scrollbar handle from the scrollbar size
 // and the handles offset and size.
@@ -0,0 +1,492 @@
+use serialize::json;
+
+use blendish::*;
+use blendish::themed_draw::ThemedDraw;
+use blendish::widget::*;
+use oui::*;
+use oui::geom::{Vec2, Rect};
+
+/// one end of a wire: a node's input or output socket
+#[deriving(Clone, PartialEq, Show, Encodable, Decodable)]
+pub struct Port {
+    pub node: uint,
+    pub output: bool,
+    // counts the node's inputs, or its outputs
+    pub index: uint,
+}
+
+impl Port {
+    pub fn input(node: uint, index: uint) -> Port {
+        Port { node: node, output: false, index: index }
+    }
+
+    pub fn output(node: uint, index: uint) -> Port {
+        Port { node: node, output: true, index: index }
+    }
+}
+
+/// a wire from an output to an input
+#[deriving(Clone, PartialEq, Show, Encodable, Decodable)]
+pub struct Link {
+    pub from: Port,
+    pub to: Port,
+}
+
+#[deriving(Clone, PartialEq, Show, Encodable, Decodable)]
+pub struct Node {
+    pub title: String,
+    pub iconid: i32,
+    // title bar color, rgb 0..1
+    pub color: (f32, f32, f32),
+    // upper left corner, relative to the editor
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    // socket names; outputs are listed first, then inputs
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+}
+
+impl Node {
+    pub fn new(title: &str, x: f32, y: f32, w: f32) -> Node {
+        Node {
+            title: title.to_string(),
+            iconid: -1,
+            color: (0.392, 0.392, 0.392),
+            x: x, y: y, w: w,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    pub fn input(mut self, name: &str) -> Node {
+        self.inputs.push(name.to_string());
+        self
+    }
+
+    pub fn output(mut self, name: &str) -> Node {
+        self.outputs.push(name.to_string());
+        self
+    }
+
+    pub fn color(mut self, r: f32, g: f32, b: f32) -> Node {
+        self.color = (r, g, b);
+        self
+    }
+
+    pub fn rect(&self) -> Rect {
+        let rows = (self.inputs.len() + self.outputs.len()) as f32;
+        Rect { x: self.x, y: self.y, w: self.w,
+            h: NODE_MARGIN_TOP + rows * WIDGET_HEIGHT as f32 + NODE_MARGIN_DOWN }
+    }
+
+    // the socket's row, top to bottom
+    fn row(&self, port: &Port) -> uint {
+        if port.output { port.index } else { self.outputs.len() + port.index }
+    }
+}
+
+/// nodes and the wires between them; what a node editor edits. later
+/// nodes are drawn over earlier ones.
+#[deriving(Clone, PartialEq, Show, Encodable, Decodable)]
+pub struct NodeGraph {
+    pub nodes: Vec<Node>,
+    pub links: Vec<Link>,
+}
+
+impl NodeGraph {
+    pub fn new() -> NodeGraph {
+        NodeGraph { nodes: Vec::new(), links: Vec::new() }
+    }
+
+    pub fn add_node(&mut self, node: Node) -> uint {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    pub fn has_port(&self, port: &Port) -> bool {
+        if port.node >= self.nodes.len() { return false; }
+        let node = &self.nodes[port.node];
+        port.index < if port.output { node.outputs.len() } else { node.inputs.len() }
+    }
+
+    /// the center of a socket: on the right edge for outputs, the left
+    /// for inputs
+    pub fn port_pos(&self, port: &Port) -> Vec2 {
+        let node = &self.nodes[port.node];
+        let y = node.y + NODE_MARGIN_TOP + (node.row(port) as f32 + 0.5) * WIDGET_HEIGHT as f32;
+        Vec2 { x: if port.output { node.x + node.w } else { node.x }, y: y }
+    }
+
+    /// the socket within NODE_PORT_HIT_RADIUS of 'at', topmost first
+    pub fn port_at(&self, at: Vec2) -> Option<Port> {
+        let r2 = NODE_PORT_HIT_RADIUS * NODE_PORT_HIT_RADIUS;
+        for n in range(0, self.nodes.len()).rev() {
+            let node = &self.nodes[n];
+            let ports = node.outputs.iter().enumerate().map(|(i, _)| Port::output(n, i))
+                .chain(node.inputs.iter().enumerate().map(|(i, _)| Port::input(n, i)));
+            for port in ports {
+                let p = self.port_pos(&port);
+                let (dx, dy) = (at.x - p.x, at.y - p.y);
+                if dx*dx + dy*dy <= r2 { return Some(port); }
+            }
+        }
+        None
+    }
+
+    /// the topmost node under 'at'
+    pub fn node_at(&self, at: Vec2) -> Option<uint> {
+        range(0, self.nodes.len()).rev().find(|&n| {
+            let r = self.nodes[n].rect();
+            at.x >= r.x && at.y >= r.y && at.x < r.x + r.w && at.y < r.y + r.h
+        })
+    }
+
+    /// the wire into an input, if any; an input takes one
+    pub fn link_into(&self, input: &Port) -> Option<uint> {
+        self.links.iter().position(|l| l.to == *input)
+    }
+
+    /// wire an output to an input, given either way round, replacing the
+    /// input's wire; false if that's no wire (two inputs, a loop back into
+    /// the same node, a socket that isn't there) or it's there already
+    pub fn connect(&mut self, a: &Port, b: &Port) -> bool {
+        let (from, to) = if a.output { (a, b) } else { (b, a) };
+        if !from.output || to.output || from.node == to.node
+        || !self.has_port(from) || !self.has_port(to) {
+            return false;
+        }
+        let link = Link { from: from.clone(), to: to.clone() };
+        match self.link_into(to) {
+            Some(i) if self.links[i] == link => return false,
+            Some(i) => { self.links.remove(i); }
+            None => {}
+        }
+        self.links.push(link);
+        true
+    }
+
+    /// unplug the wire into an input, returning it
+    pub fn disconnect(&mut self, input: &Port) -> Option<Link> {
+        match self.link_into(input) {
+            Some(i) => self.links.remove(i),
+            None => None
+        }
+    }
+
+    /// move a node over all the others; returns its new index, which is
+    /// the last. wires follow it.
+    pub fn raise(&mut self, node: uint) -> uint {
+        let last = self.nodes.len() - 1;
+        if node == last { return node; }
+        let moved = self.nodes.remove(node).unwrap();
+        self.nodes.push(moved);
+        // the nodes after it move down one
+        let renumber = |n: uint| if n == node { last } else if n > node { n - 1 } else { n };
+        for link in self.links.iter_mut() {
+            link.from.node = renumber(link.from.node);
+            link.to.node = renumber(link.to.node);
+        }
+        last
+    }
+
+    pub fn to_json(&self) -> String {
+        json::encode(self)
+    }
+
+    pub fn from_json(s: &str) -> Option<NodeGraph> {
+        json::decode(s).ok()
+    }
+}
+
+/// what a press in a node editor is doing
+#[deriving(Clone, PartialEq, Show)]
+pub enum NodeEdit {
+    NODE_IDLE,
+    // moving a node, from where it was
+    NODE_MOVE(uint, f32, f32),
+    // pulling a wire out of a socket, its loose end at the cursor
+    NODE_WIRE(Port),
+}
+
+/// a node editor for 'graph': drag nodes by their body, drag from a
+/// socket to another to wire them, and drag a wire off an input to
+/// unplug it. each press is one undo step.
+pub fn node_editor(ui: &mut Context<Widget>, parent: Item, tag: Tag,
+    graph: Property<NodeGraph>)
+-> Item
+{
+    ui.watch(&graph);
+    let item = ui.item(NodeEditor { graph: graph, selected: None, edit: NODE_IDLE,
+        start: NodeGraph::new() });
+    ui.set_tag(item, tag);
+    ui.set_handler(item, Some(nodeeditorhandler), BUTTON0_DOWN|BUTTON0_CAPTURE|BUTTON0_UP);
+    ui.append(parent, item);
+    return item;
+}
+
+pub fn nodeeditorhandler(ui: &mut Context<Widget>, item: Item, event: EventFlags) {
+    let rect = ui.get_abs_rect(item);
+    let cursor = ui.get_cursor();
+    let at = Vec2 { x: cursor.x - rect.x, y: cursor.y - rect.y };
+    let delta = ui.get_cursor_start_delta();
+
+    let mut change = None;
+    {
+        match *ui.get_widget(item) {
+            NodeEditor { graph:ref graph, selected:ref mut selected, edit:ref mut edit,
+                start:ref mut start } => {
+                match event {
+                    BUTTON0_DOWN => {
+                        let mut g = graph.get();
+                        *start = g.clone();
+                        *edit = match g.port_at(at) {
+                            Some(ref port) if port.output => NODE_WIRE(port.clone()),
+                            // an input's wire comes away with the cursor
+                            Some(port) => match g.disconnect(&port) {
+                                Some(link) => NODE_WIRE(link.from),
+                                None => NODE_WIRE(port)
+                            },
+                            None => match g.node_at(at) {
+                                Some(n) => {
+                                    let n = g.raise(n);
+                                    *selected = Some(n);
+                                    NODE_MOVE(n, g.nodes[n].x, g.nodes[n].y)
+                                }
+                                None => {
+                                    *selected = None;
+                                    NODE_IDLE
+                                }
+                            }
+                        };
+                        graph.set(g);
+                    }
+                    BUTTON0_CAPTURE => match *edit {
+                        NODE_MOVE(n, x, y) => {
+                            let mut g = graph.get();
+                            {
+                                let node = g.nodes.get_mut(n);
+                                node.x = x + delta.x;
+                                node.y = y + delta.y;
+                            }
+                            graph.set(g);
+                        }
+                        _ => {}
+                    },
+                    BUTTON0_UP => {
+                        match *edit {
+                            NODE_WIRE(ref port) => {
+                                let mut g = graph.get();
+                                match g.port_at(at) {
+                                    Some(other) => { g.connect(port, &other); }
+                                    None => {}
+                                }
+                                graph.set(g);
+                            }
+                            _ => {}
+                        }
+                        let after = graph.get();
+                        // a click only raises the node, and that's no edit
+                        let still = match *edit {
+                            NODE_MOVE(n, x, y) => after.nodes[n].x == x && after.nodes[n].y == y,
+                            _ => false
+                        };
+                        *edit = NODE_IDLE;
+                        // the whole press is one undo step
+                        if after != *start && !still {
+                            change = Some(SetProperty::new(graph.clone(), start.clone(), after));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    match change {
+        Some(cmd) => ui.push_command(box cmd),
+        None => {}
+    }
+    ui.request_redraw();
+}
+
+/// draw 'graph' in (x, y, w, h): the wires, then the nodes over them.
+/// 'cursor' is relative to (x, y), and lights up the node under it, if
+/// 'state' says it's over the editor; 'edit' may be a wire on its way.
+pub fn draw_node_graph(vg: &mut ThemedContext, graph: &NodeGraph, x: f32, y: f32,
+    w: f32, h: f32, state: ItemState, selected: Option<uint>, edit: &NodeEdit, cursor: Vec2)
+{
+    let hot = if state == COLD { None } else { graph.node_at(cursor) };
+    let node_state = |n: uint| {
+        if Some(n) == selected { ACTIVE }
+        else if Some(n) == hot { HOT }
+        else { COLD }
+    };
+
+    // narrowing, not replacing, the clip of a canvas or panel it's in
+    vg.nvg().save();
+    vg.nvg().intersect_scissor(x, y, w, h);
+
+    for link in graph.links.iter() {
+        let p0 = graph.port_pos(&link.from);
+        let p1 = graph.port_pos(&link.to);
+        vg.draw_node_wire(x + p0.x, y + p0.y, x + p1.x, y + p1.y,
+            node_state(link.from.node), node_state(link.to.node));
+    }
+    match *edit {
+        NODE_WIRE(ref port) => {
+            // drawn from output to input, whichever end it started at
+            let p = graph.port_pos(port);
+            let (p0, p1) = if port.output { (p, cursor) } else { (cursor, p) };
+            vg.draw_node_wire(x + p0.x, y + p0.y, x + p1.x, y + p1.y, ACTIVE, ACTIVE);
+        }
+        _ => {}
+    }
+
+    let line = WIDGET_HEIGHT as f32;
+    for (n, node) in graph.nodes.iter().enumerate() {
+        let r = node.rect();
+        let (nx, ny) = (x + r.x, y + r.y);
+        let (red, green, blue) = node.color;
+        vg.draw_node_background(nx, ny, r.w, r.h, node_state(n),
+            node.iconid as u32, node.title.as_slice(), rgba_f(red, green, blue, 1.0));
+        for (i, name) in node.outputs.iter().enumerate() {
+            let port = Port::output(n, i);
+            let p = graph.port_pos(&port);
+            vg.draw_node_port_label(nx, y + p.y - line*0.5, r.w, line, true, name.as_slice());
+            vg.draw_node_port(x + p.x, y + p.y, COLD, offset_color(rgba_f(red, green, blue, 1.0), 30));
+        }
+        for (i, name) in node.inputs.iter().enumerate() {
+            let port = Port::input(n, i);
+            let p = graph.port_pos(&port);
+            vg.draw_node_port_label(nx, y + p.y - line*0.5, r.w, line, false, name.as_slice());
+            vg.draw_node_port(x + p.x, y + p.y, COLD, offset_color(rgba_f(red, green, blue, 1.0), 30));
+        }
+    }
+
+    vg.nvg().restore();
+}
+
+#[cfg(test)]
+fn test_graph() -> NodeGraph {
+    let mut graph = NodeGraph::new();
+    graph.add_node(Node::new("Texture", 10.0, 10.0, 120.0).output("Color").output("Alpha"));
+    graph.add_node(Node::new("Mix", 200.0, 40.0, 120.0).input("A").input("B").output("Result"));
+    graph
+}
+
+#[test]
+fn test_connect_replaces_the_inputs_wire() {
+    let mut graph = test_graph();
+    assert!(graph.connect(&Port::output(0, 0), &Port::input(1, 0)));
+    // either way round
+    assert!(graph.connect(&Port::input(1, 0), &Port::output(0, 1)));
+    assert_eq!(graph.links.len(), 1);
+    assert_eq!(graph.links[0].from, Port::output(0, 1));
+    // no wires between inputs, into the same node, or to nowhere
+    assert!(!graph.connect(&Port::input(1, 0), &Port::input(1, 1)));
+    assert!(!graph.connect(&Port::output(1, 0), &Port::input(1, 1)));
+    assert!(!graph.connect(&Port::output(0, 0), &Port::input(1, 5)));
+    assert!(graph.disconnect(&Port::input(1, 0)).is_some());
+    assert!(graph.links.is_empty());
+}
+
+#[test]
+fn test_raise_keeps_the_wires() {
+    let mut graph = test_graph();
+    graph.connect(&Port::output(0, 0), &Port::input(1, 1));
+    assert_eq!(graph.raise(0), 1);
+    assert_eq!(graph.nodes[1].title.as_slice(), "Texture");
+    assert_eq!(graph.links[0], Link { from: Port::output(1, 0), to: Port::input(0, 1) });
+}
+
+#[test]
+fn test_graph_survives_json() {
+    let mut graph = test_graph();
+    graph.connect(&Port::output(0, 1), &Port::input(1, 0));
+    let text = graph.to_json();
+    assert_eq!(NodeGraph::from_json(text.as_slice()), Some(graph));
+    assert_eq!(NodeGraph::from_json("{\"nodes\": 3}"), None);
+}
+
+#[test]
+fn test_wire_and_unplug_with_the_mouse() {
+    use blendish::driver::Driver;
+
+    let graph = Property::new(test_graph());
+    let mut ui: Context<Widget> = Context::create_context();
+    let root = ui.item(Panel { unused: 0 });
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 400, 300);
+    let editor = node_editor(&mut ui, root, 1, graph.clone());
+    ui.set_layout(editor, FILL);
+    ui.layout();
+
+    let mut driver = Driver::new(&mut ui);
+    let out = graph.get().port_pos(&Port::output(0, 0));
+    let input = graph.get().port_pos(&Port::input(1, 0));
+    driver.move_to(out.x, out.y);
+    driver.press();
+    driver.move_to(input.x, input.y);
+    driver.release();
+    assert_eq!(graph.get().links, vec![Link { from: Port::output(0, 0), to: Port::input(1, 0) }]);
+
+    // pulled off the input and dropped in the open: unplugged
+    driver.move_to(input.x, input.y);
+    driver.press();
+    driver.move_to(input.x - 60.0, input.y + 120.0);
+    driver.release();
+    assert!(graph.get().links.is_empty());
+    assert!(driver.ui.undo());
+    assert_eq!(graph.get().links.len(), 1);
+}
+
+#[test]
+fn test_drag_a_node() {
+    use blendish::driver::Driver;
+
+    let graph = Property::new(test_graph());
+    let mut ui: Context<Widget> = Context::create_context();
+    let root = ui.item(Panel { unused: 0 });
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 400, 300);
+    let editor = node_editor(&mut ui, root, 1, graph.clone());
+    ui.set_layout(editor, FILL);
+    ui.layout();
+
+    let mut driver = Driver::new(&mut ui);
+    // by its title bar
+    driver.move_to(60.0, 20.0);
+    driver.press();
+    driver.move_to(80.0, 50.0);
+    driver.move_to(90.0, 70.0);
+    driver.release();
+    // the dragged node is on top, and wherever it was dropped
+    let moved = graph.get();
+    assert_eq!(moved.nodes[1].title.as_slice(), "Texture");
+    assert_eq!((moved.nodes[1].x, moved.nodes[1].y), (40.0, 60.0));
+    assert!(driver.ui.undo());
+    assert_eq!(graph.get(), test_graph());
+}
+
+#[test]
+fn test_click_is_no_undo_step() {
+    use blendish::driver::Driver;
+
+    let graph = Property::new(test_graph());
+    let mut ui: Context<Widget> = Context::create_context();
+    let root = ui.item(Panel { unused: 0 });
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 400, 300);
+    let editor = node_editor(&mut ui, root, 1, graph.clone());
+    ui.set_layout(editor, FILL);
+    ui.layout();
+
+    // the lower node comes up, but nothing was edited
+    let mut driver = Driver::new(&mut ui);
+    driver.move_to(60.0, 20.0);
+    driver.press();
+    driver.release();
+    assert_eq!(graph.get().nodes[1].title.as_slice(), "Texture");
+    assert!(!driver.ui.can_undo());
+}
@@ -0,0 +1,108 @@
+use blendish::*;
+use blendish::widget::*;
+use oui::*;
+
+/// a scrollbar setting 'offset' (0..1), with a handle 'size' (0..1) long;
+/// vertical if it's taller than wide. drag the handle, or click the track
+/// on either side of it to page.
+pub fn scrollbar(ui: &mut Context<Widget>, parent: Item, tag: Tag,
+    offset: Property<f32>, size: f32)
+-> Item
+{
+    ui.watch(&offset);
+    let item = ui.item(ScrollBar { offset: offset, size: size, panel: Item::none(), grab: -1.0 });
+    ui.set_tag(item, tag);
+    ui.set_size(item, SCROLLBAR_WIDTH, 0);
+    ui.set_handler(item, Some(scrollbarhandler), BUTTON0_DOWN|BUTTON0_CAPTURE|BUTTON0_UP);
+    ui.append(parent, item);
+    return item;
+}
+
+/// a 'height' tall view of a column that may be longer; returns the
+/// column, to append to. 'offset' (0..1) is how far it's scrolled, and
+/// a scrollbar at the right sets it, its handle as long as the view is
+/// of the column.
+pub fn scrollpanel(ui: &mut Context<Widget>, parent: Item, tag: Tag, height: u32,
+    offset: Property<f32>)
+-> Item
+{
+    let panel = ui.item(ScrollPanel { offset: offset.clone() });
+    ui.set_tag(panel, tag);
+    ui.set_size(panel, 0, height);
+    ui.bind_scroll(panel, &offset);
+    ui.append(parent, panel);
+
+    let content = column(ui, panel);
+    ui.set_layout(content, HFILL|TOP);
+    ui.set_margins(content, 0, 0, SCROLLBAR_WIDTH as i32, 0);
+
+    // pinned, so it stays put while the column scrolls
+    let bar = ui.item(ScrollBar { offset: offset.clone(), size: 1.0, panel: panel, grab: -1.0 });
+    ui.set_size(bar, SCROLLBAR_WIDTH, height);
+    ui.set_handler(bar, Some(scrollbarhandler), BUTTON0_DOWN|BUTTON0_CAPTURE|BUTTON0_UP);
+    ui.append(panel, bar);
+    ui.set_anchor(bar, panel, RIGHT|TOP, 0, 0);
+    ui.watch(&offset);
+    return content;
+}
+
+/// the length of a scrollbar's handle, 0..1: the part of its panel's
+/// content in view, or the size it was given
+pub fn handle_size(ui: &mut Context<Widget>, item: Item) -> f32 {
+    let (panel, size) = match *ui.get_widget(item) {
+        ScrollBar { offset:_, size:size, panel:panel, grab:_ } => (panel, size),
+        _ => return 1.0
+    };
+    if panel.invalid() { return size; }
+    let view = ui.get_rect(panel).h;
+    let content = ui.get_content_size(panel).y;
+    if content > view { view / content } else { 1.0 }
+}
+
+pub fn scrollbarhandler(ui: &mut Context<Widget>, item: Item, event: EventFlags) {
+    let (offset, grab) = match *ui.get_widget(item) {
+        ScrollBar { offset:ref offset, size:_, panel:_, grab:grab } => (offset.clone(), grab),
+        _ => return
+    };
+    let size = handle_size(ui, item);
+    let rect = ui.get_abs_rect(item);
+    let (mut hx, mut hy, mut hw, mut hh) = (rect.x, rect.y, rect.w, rect.h);
+    scroll_handle_rect(&mut hx, &mut hy, &mut hw, &mut hh, offset.get(), size);
+
+    // along the track, relative to its start
+    let cursor = ui.get_cursor();
+    let vertical = rect.h > rect.w;
+    let (pos, len, start, end) = if vertical {
+        (cursor.y - rect.y, rect.h, hy - rect.y, hy + hh - rect.y)
+    } else {
+        (cursor.x - rect.x, rect.w, hx - rect.x, hx + hw - rect.x)
+    };
+
+    let grab = match event {
+        BUTTON0_DOWN => {
+            if pos >= start && pos < end {
+                offset.get()
+            } else {
+                // a page is a view's length of the content
+                let page = if size < 1.0 { size / (1.0 - size) } else { 0.0 };
+                let dir = if pos < start { -1.0 } else { 1.0 };
+                offset.set(clamp(offset.get() + dir*page, 0.0, 1.0));
+                -1.0
+            }
+        }
+        BUTTON0_CAPTURE => {
+            let travel = len - (end - start);
+            if grab >= 0.0 && travel > 0.0 {
+                let delta = ui.get_cursor_start_delta();
+                let moved = if vertical { delta.y } else { delta.x };
+                offset.set(clamp(grab + moved / travel, 0.0, 1.0));
+            }
+            grab
+        }
+        _ => -1.0
+    };
+    match *ui.get_widget(item) {
+        ScrollBar { offset:_, size:_, panel:_, grab:ref mut g } => *g = grab,
+        _ => {}
+    }
+}
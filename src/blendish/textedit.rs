@@ -0,0 +1,203 @@
+use std::char;
+use oui::{
+    Key,
+        KEY_ARROW_LEFT, KEY_ARROW_RIGHT, KEY_HOME, KEY_END,
+        KEY_BACKSPACE, KEY_DELETE, KEY_ENTER, KEY_ESCAPE,
+    KeyMods,
+        MOD_SHIFT, MOD_CTRL,
+};
+
+/// what a key did to the text being edited
+#[deriving(PartialEq, Show)]
+pub enum EditAction {
+    // the text changed
+    EDITED,
+    // only the caret or selection moved
+    MOVED,
+    // Enter: keep the text
+    COMMIT,
+    // Escape: go back to the text editing started with
+    CANCEL,
+    // not a key for editing
+    IGNORED,
+}
+
+/// the state of a text being edited: the text, a caret and a selection.
+/// indices are byte offsets, always on character boundaries.
+pub struct TextEdit {
+    pub text: String,
+    // the text editing started with, for cancel()
+    pub original: String,
+    pub caret: uint,
+    // the other end of the selection; == caret when nothing's selected
+    pub anchor: uint,
+}
+
+impl TextEdit {
+    /// start editing 'text', with all of it selected
+    pub fn new(text: &str) -> TextEdit {
+        TextEdit {
+            text: text.to_string(),
+            original: text.to_string(),
+            caret: text.len(),
+            anchor: 0,
+        }
+    }
+
+    /// the selection as (begin, end); begin == end for just a caret
+    pub fn selection(&self) -> (uint, uint) {
+        if self.caret < self.anchor { (self.caret, self.anchor) }
+        else { (self.anchor, self.caret) }
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.caret != self.anchor
+    }
+
+    /// put the caret at 'pos'; with 'extend', the selection grows to it
+    pub fn move_to(&mut self, pos: uint, extend: bool) {
+        self.caret = pos;
+        if !extend { self.anchor = pos; }
+    }
+
+    /// replace the selection with 'c'
+    pub fn insert(&mut self, c: char) {
+        if char::is_control(c) { return; }
+        self.delete_selection();
+        let mut s = String::from_str(self.text.as_slice().slice_to(self.caret));
+        s.push(c);
+        s.push_str(self.text.as_slice().slice_from(self.caret));
+        self.text = s;
+        self.caret += c.len_utf8_bytes();
+        self.anchor = self.caret;
+    }
+
+    /// handle an editing key
+    pub fn key(&mut self, key: Key, mods: KeyMods) -> EditAction {
+        let shift = mods.contains(MOD_SHIFT);
+        let word = mods.contains(MOD_CTRL);
+        match key {
+            KEY_ARROW_LEFT => {
+                let pos =
+                    if self.has_selection() && !shift { self.selection().val0() }
+                    else if word { self.word_left(self.caret) }
+                    else { self.char_left(self.caret) };
+                self.move_to(pos, shift);
+                MOVED
+            }
+            KEY_ARROW_RIGHT => {
+                let pos =
+                    if self.has_selection() && !shift { self.selection().val1() }
+                    else if word { self.word_right(self.caret) }
+                    else { self.char_right(self.caret) };
+                self.move_to(pos, shift);
+                MOVED
+            }
+            KEY_HOME => { self.move_to(0, shift); MOVED }
+            KEY_END => {
+                let end = self.text.len();
+                self.move_to(end, shift);
+                MOVED
+            }
+            KEY_BACKSPACE => {
+                if !self.has_selection() {
+                    self.anchor =
+                        if word { self.word_left(self.caret) }
+                        else { self.char_left(self.caret) };
+                }
+                if self.delete_selection() { EDITED } else { MOVED }
+            }
+            KEY_DELETE => {
+                if !self.has_selection() {
+                    self.anchor =
+                        if word { self.word_right(self.caret) }
+                        else { self.char_right(self.caret) };
+                }
+                if self.delete_selection() { EDITED } else { MOVED }
+            }
+            KEY_ENTER => COMMIT,
+            KEY_ESCAPE => {
+                self.text = self.original.clone();
+                let end = self.text.len();
+                self.move_to(end, false);
+                CANCEL
+            }
+            _ => IGNORED
+        }
+    }
+
+    // remove the selected text; false if there was none
+    fn delete_selection(&mut self) -> bool {
+        let (begin, end) = self.selection();
+        if begin == end { return false; }
+        let mut s = String::from_str(self.text.as_slice().slice_to(begin));
+        s.push_str(self.text.as_slice().slice_from(end));
+        self.text = s;
+        self.caret = begin;
+        self.anchor = begin;
+        true
+    }
+
+    fn char_left(&self, pos: uint) -> uint {
+        match self.text.as_slice().slice_to(pos).char_indices().last() {
+            Some((i, _)) => i,
+            None => 0
+        }
+    }
+
+    fn char_right(&self, pos: uint) -> uint {
+        match self.text.as_slice().slice_from(pos).chars().next() {
+            Some(c) => pos + c.len_utf8_bytes(),
+            None => pos
+        }
+    }
+
+    // to the start of the word left of 'pos'
+    fn word_left(&self, pos: uint) -> uint {
+        let mut pos = pos;
+        // skip spaces, then the word
+        while pos > 0 && char::is_whitespace(self.char_before(pos)) {
+            pos = self.char_left(pos);
+        }
+        while pos > 0 && !char::is_whitespace(self.char_before(pos)) {
+            pos = self.char_left(pos);
+        }
+        pos
+    }
+
+    // past the end of the word right of 'pos', and the spaces after it
+    fn word_right(&self, pos: uint) -> uint {
+        let len = self.text.len();
+        let mut pos = pos;
+        while pos < len && !char::is_whitespace(self.char_at(pos)) {
+            pos = self.char_right(pos);
+        }
+        while pos < len && char::is_whitespace(self.char_at(pos)) {
+            pos = self.char_right(pos);
+        }
+        pos
+    }
+
+    fn char_before(&self, pos: uint) -> char {
+        self.text.as_slice().slice_to(pos).chars().last().unwrap()
+    }
+
+    fn char_at(&self, pos: uint) -> char {
+        self.text.as_slice().slice_from(pos).chars().next().unwrap()
+    }
+}
+
+/// the caret position nearest to 'x', given the positions from
+/// LowLevelDraw::text_caret_positions
+pub fn caret_at(carets: &[(uint, f32)], x: f32) -> uint {
+    let mut best = 0u;
+    let mut best_dist = -1.0f32;
+    for &(index, cx) in carets.iter() {
+        let dist = (cx - x).abs();
+        if best_dist < 0.0 || dist < best_dist {
+            best = index;
+            best_dist = dist;
+        }
+    }
+    best
+}
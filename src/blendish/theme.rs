@@ -26,6 +26,26 @@ pub struct WidgetTheme
     pub shadeDown: i32,
 }
 
+/// describes the theme used to draw nodes
+#[repr(C)]
+pub struct NodeTheme
+{
+    /// inner color of selected node (and downarrow)
+    pub nodeSelectedColor: Color,
+    /// outline of wires
+    pub wiresColor: Color,
+    /// color of text label when active
+    pub textSelectedColor: Color,
+    /// inner color of active node (and dragged wire)
+    pub activeNodeColor: Color,
+    /// color of selected wire
+    pub wireSelectColor: Color,
+    /// color of background of node
+    pub nodeBackdropColor: Color,
+    /// how much a noodle curves (0 to 10)
+    pub noodleCurving: i32,
+}
+
 /// describes the theme used to draw widgets
 #[repr(C)]
 pub struct Theme
@@ -58,6 +78,8 @@ pub struct Theme
     pub menuTheme: WidgetTheme,
     /// theme for menu items
     pub menuItemTheme: WidgetTheme,
+    /// theme for nodes
+    pub nodeTheme: NodeTheme,
 }
 
 
@@ -260,5 +282,14 @@ pub fn initial_theme() -> Theme {
             shadeTop: 38,
             shadeDown: 0,
         },
+        nodeTheme: NodeTheme {
+            nodeSelectedColor: rgba_f( 0.945, 0.345, 0.0, 1.0 ),
+            wiresColor: rgba_f( 0.0, 0.0, 0.0, 1.0 ),
+            textSelectedColor: rgba_f( 0.498, 0.439, 0.439, 1.0 ),
+            activeNodeColor: rgba_f( 1.0, 0.667, 0.251, 1.0 ),
+            wireSelectColor: rgba_f( 1.0, 1.0, 1.0, 1.0 ),
+            nodeBackdropColor: rgba_f( 0.608, 0.608, 0.608, 0.627 ),
+            noodleCurving: 5,
+        },
     }
 }
@@ -1,5 +1,5 @@
 
-use nanovg::{Color, LEFT,CENTER };
+use nanovg::{Color, LEFT,CENTER,RIGHT,BASELINE };
 use util::{transparent};
 use super::constants::*;
 use super::*;
@@ -22,7 +22,18 @@ pub trait ThemedDraw
     fn draw_menu_background(&mut self, x:f32,y:f32, w:f32,h:f32, flags: CornerFlags);
     fn draw_menu_label(&mut self, x:f32,y:f32, w:f32,h:f32, iconid: u32, label: &str);
     fn draw_menu_item(&mut self, x:f32,y:f32, w:f32,h:f32, state: &mut ItemState, iconid: u32, label: &str);
+    fn draw_menu_separator(&mut self, x:f32,y:f32, w:f32,h:f32);
+    fn draw_menu_check(&mut self, x:f32,y:f32, w:f32,h:f32, state: &mut ItemState, checked: bool, label: &str);
+    fn draw_menu_shortcut(&mut self, x:f32,y:f32, w:f32,h:f32, state: ItemState, shortcut: &str);
+    fn draw_submenu_arrow(&mut self, x:f32,y:f32, w:f32,h:f32, state: ItemState);
+    fn draw_tooltip(&mut self, x:f32,y:f32, w:f32,h:f32, text: &str, shortcut: &str, description: &str);
     fn draw_tooltip_background(&mut self, x:f32,y:f32, w:f32,h:f32);
+    fn draw_node_port(&mut self, x:f32,y:f32, state: ItemState, color: Color);
+    fn draw_node_wire(&mut self, x0:f32,y0:f32, x1:f32,y1:f32, state0: ItemState, state1: ItemState);
+    fn draw_colored_node_wire(&mut self, x0:f32,y0:f32, x1:f32,y1:f32, color0: Color, color1: Color);
+    fn draw_node_background(&mut self, x:f32,y:f32, w:f32,h:f32, state: ItemState, iconid: u32, label: &str, title_color: Color);
+    fn draw_node_port_label(&mut self, x:f32,y:f32, w:f32,h:f32, output: bool, label: &str);
+    fn draw_canvas_grid(&mut self, x:f32,y:f32, w:f32,h:f32, ox:f32,oy:f32, step:f32);
 }
 
 impl<'a> ThemedDraw for ThemedContext<'a>
@@ -307,9 +318,11 @@ impl<'a> ThemedDraw for ThemedContext<'a>
             shade_down = offset_color(
                 self.theme().sliderTheme.itemColor, top);
         }
-        self.nvg().scissor(x, y, 8.0+(w-8.0)*clamp(progress, 0.0, 1.0), h);
+        // intersect, and save and restore, so an enclosing clip holds
+        self.nvg().save();
+        self.nvg().intersect_scissor(x, y, 8.0+(w-8.0)*clamp(progress, 0.0, 1.0), h);
         self.nvg().draw_inner_box(x, y, w, h, cr[0], cr[1], cr[2], cr[3], shade_top, shade_down);
-        self.nvg().reset_scissor();
+        self.nvg().restore();
 
         let outline = self.theme().sliderTheme.outlineColor;
         self.nvg().draw_outline_box(x, y, w, h, cr[0], cr[1], cr[2], cr[3],
@@ -432,6 +445,62 @@ impl<'a> ThemedDraw for ThemedContext<'a>
             color, LEFT, &font, LABEL_FONT_SIZE, label, None);
     }
 
+    /// Draw a menu separator, a faint line across the middle of (x, y, w, h)
+    fn draw_menu_separator(&mut self, x:f32,y:f32, w:f32,h:f32)
+    {
+        let color = self.theme().menuTheme.textColor;
+        let y = (y + h*0.5).floor() + 0.5;
+        let nvg = self.nvg();
+        nvg.begin_path();
+        nvg.move_to(x + MENU_SEPARATOR_INSET, y);
+        nvg.line_to(x + w - MENU_SEPARATOR_INSET, y);
+        nvg.stroke_width(1.0);
+        nvg.stroke_color(rgba_f(color.r(), color.g(), color.b(), color.a()*MENU_SEPARATOR_ALPHA));
+        nvg.stroke();
+    }
+
+    /// Draw a menu item with a check mark (if checked) in place of the icon,
+    /// where state denotes the widgets current UI state.
+    fn draw_menu_check(&mut self,
+        x:f32,y:f32, w:f32,h:f32, state: &mut ItemState,
+        checked: bool, label: &str
+    ) {
+        self.draw_menu_item(x, y, w, h, state, -1 as u32, "");
+        let color = text_color(&self.theme().menuItemTheme, *state);
+        if checked {
+            self.nvg().draw_check(x, y+h-OPTION_HEIGHT-3.0, color);
+        }
+        let icons = *self.icon_image();
+        let font  = *self.font();
+        let pad = ICON_SHEET_RES as f32;
+        self.nvg().draw_icon_label_value(x+pad, y, w-pad, h, &icons, -1 as u32,
+            color, LEFT, &font, LABEL_FONT_SIZE, label, None);
+    }
+
+    /// Draw a menu item's keyboard shortcut, right-aligned in (x, y, w, h)
+    fn draw_menu_shortcut(&mut self,
+        x:f32,y:f32, w:f32,h:f32, state: ItemState, shortcut: &str
+    ) {
+        let color = transparent(text_color(&self.theme().menuItemTheme, state));
+        let font  = *self.font();
+        let nvg = self.nvg();
+        nvg.font_face_id(&font);
+        nvg.font_size(LABEL_FONT_SIZE);
+        nvg.begin_path();
+        nvg.fill_color(color);
+        nvg.text_align(RIGHT|BASELINE);
+        nvg.text(x+w-PAD_RIGHT as f32, y+h-TEXT_PAD_DOWN as f32, shortcut);
+    }
+
+    /// Draw the arrow of a menu item that opens a submenu, at the right
+    /// of (x, y, w, h)
+    fn draw_submenu_arrow(&mut self,
+        x:f32,y:f32, w:f32,h:f32, state: ItemState
+    ) {
+        let color = transparent(text_color(&self.theme().menuItemTheme, state));
+        self.nvg().draw_arrow(x+w-PAD_RIGHT as f32, y+h*0.5, NUMBER_ARROW_SIZE, color);
+    }
+
     /// Draw a tooltip background with its lower left origin at (x, y) and size of (w, h)
     fn draw_tooltip_background(&mut self, x:f32,y:f32, w:f32,h:f32
     ) {
@@ -450,4 +519,164 @@ impl<'a> ThemedDraw for ThemedContext<'a>
         self.nvg().draw_drop_shadow(x, y, w, h, MENU_RADIUS,
             SHADOW_FEATHER, SHADOW_ALPHA);
     }
+
+    /// Draw a tooltip in (x, y, w, h): its text and shortcut on the first
+    /// line, and the description, if any, on the second
+    fn draw_tooltip(&mut self,
+        x:f32,y:f32, w:f32,h:f32,
+        text: &str, shortcut: &str, description: &str
+    ) {
+        self.draw_tooltip_background(x, y, w, h);
+        let color = self.theme().tooltipTheme.textColor;
+        let icons = *self.icon_image();
+        let font  = *self.font();
+        let line = WIDGET_HEIGHT as f32;
+        self.nvg().draw_icon_label_value(x, y, w, line, &icons, -1 as u32,
+            color, LEFT, &font, LABEL_FONT_SIZE, text, None);
+        if shortcut.len() > 0 {
+            let nvg = self.nvg();
+            nvg.font_face_id(&font);
+            nvg.font_size(LABEL_FONT_SIZE);
+            nvg.begin_path();
+            nvg.fill_color(transparent(color));
+            nvg.text_align(RIGHT|BASELINE);
+            nvg.text(x+w-PAD_RIGHT as f32, y+line-TEXT_PAD_DOWN as f32, shortcut);
+        }
+        if description.len() > 0 {
+            self.nvg().draw_icon_label_value(x, y+line, w, line, &icons, -1 as u32,
+                transparent(color), LEFT, &font, LABEL_FONT_SIZE, description, None);
+        }
+    }
+
+    /// Draw a node port at the given position filled with the given color
+    fn draw_node_port(&mut self, x:f32,y:f32, state: ItemState, color: Color)
+    {
+        let outline = self.theme().nodeTheme.wiresColor;
+        let nvg = self.nvg();
+        nvg.begin_path();
+        nvg.circle(x, y, NODE_PORT_RADIUS);
+        nvg.stroke_color(outline);
+        nvg.stroke_width(1.0);
+        nvg.stroke();
+        nvg.fill_color(if state != COLD { offset_color(color, HOVER_SHADE) } else { color });
+        nvg.fill();
+    }
+
+    /// Draw a node wire originating at (x0, y0) and floating to (x1, y1), with
+    /// a colored gradient based on the states state0 and state1:
+    /// COLD: default wire color
+    /// HOT: selected wire color
+    /// ACTIVE: dragged wire color
+    fn draw_node_wire(&mut self,
+        x0:f32,y0:f32, x1:f32,y1:f32, state0: ItemState, state1: ItemState
+    ) {
+        let color0 = node_wire_color(&self.theme().nodeTheme, state0);
+        let color1 = node_wire_color(&self.theme().nodeTheme, state1);
+        self.draw_colored_node_wire(x0, y0, x1, y1, color0, color1);
+    }
+
+    /// Draw a node wire originating at (x0, y0) and floating to (x1, y1), with
+    /// a colored gradient from color0 to color1
+    fn draw_colored_node_wire(&mut self,
+        x0:f32,y0:f32, x1:f32,y1:f32, color0: Color, color1: Color
+    ) {
+        let length = max((x1 - x0).abs(), (y1 - y0).abs());
+        let delta = length * self.theme().nodeTheme.noodleCurving as f32 / 10.0;
+        let wires = self.theme().nodeTheme.wiresColor;
+        let outline = rgba_f(wires.r(), wires.g(), wires.b(), min(color0.a(), color1.a()));
+        let nvg = self.nvg();
+        nvg.begin_path();
+        nvg.move_to(x0, y0);
+        nvg.bezier_to(x0 + delta, y0, x1 - delta, y1, x1, y1);
+        nvg.stroke_color(outline);
+        nvg.stroke_width(NODE_WIRE_OUTLINE_WIDTH);
+        nvg.stroke();
+        nvg.stroke_paint(nvg.linear_gradient(x0, y0, x1, y1, color0, color1));
+        nvg.stroke_width(NODE_WIRE_WIDTH);
+        nvg.stroke();
+    }
+
+    /// Draw a node background with its upper left origin at (x, y) and size of (w, h)
+    /// where title_color provides the base color for the title bar
+    fn draw_node_background(&mut self,
+        x:f32,y:f32, w:f32,h:f32, state: ItemState,
+        iconid: u32, label: &str, title_color: Color
+    ) {
+        self.nvg().draw_inner_box(x, y, w, NODE_TITLE_HEIGHT+2.0,
+            NODE_RADIUS, NODE_RADIUS, 0.0, 0.0,
+            transparent(offset_color(title_color, BEVEL_SHADE)),
+            transparent(title_color));
+        let backdrop = self.theme().nodeTheme.nodeBackdropColor;
+        self.nvg().draw_inner_box(x, y+NODE_TITLE_HEIGHT-1.0, w, h+2.0-NODE_TITLE_HEIGHT,
+            0.0, 0.0, NODE_RADIUS, NODE_RADIUS,
+            transparent(backdrop), transparent(backdrop));
+
+        let color = self.theme().regularTheme.textColor;
+        let icons = *self.icon_image();
+        let font  = *self.font();
+        self.nvg().draw_icon_label_value(x+NODE_ARROW_AREA_WIDTH, y,
+            w-NODE_ARROW_AREA_WIDTH-NODE_MARGIN_SIDE, NODE_TITLE_HEIGHT,
+            &icons, iconid, color, LEFT, &font, LABEL_FONT_SIZE, label, None);
+
+        let (border, arrow) = match state {
+            HOT => (self.theme().nodeTheme.nodeSelectedColor,
+                    self.theme().nodeTheme.nodeSelectedColor),
+            ACTIVE => (self.theme().nodeTheme.activeNodeColor,
+                       self.theme().nodeTheme.nodeSelectedColor),
+            _ => (black(), offset_color(title_color, -BEVEL_SHADE))
+        };
+        self.nvg().draw_outline_box(x, y, w, h+1.0,
+            NODE_RADIUS, NODE_RADIUS, NODE_RADIUS, NODE_RADIUS,
+            transparent(border));
+        self.nvg().draw_node_arrow_down(x + NODE_MARGIN_SIDE, y + NODE_TITLE_HEIGHT-4.0,
+            2.0, arrow);
+        self.nvg().draw_drop_shadow(x, y, w, h, NODE_RADIUS,
+            SHADOW_FEATHER, SHADOW_ALPHA);
+    }
+
+    /// Draw the name of a node's socket in (x, y, w, h), at the left for
+    /// an input and the right for an output
+    fn draw_node_port_label(&mut self,
+        x:f32,y:f32, w:f32,h:f32, output: bool, label: &str
+    ) {
+        let color = self.theme().regularTheme.textColor;
+        let font  = *self.font();
+        let nvg = self.nvg();
+        nvg.font_face_id(&font);
+        nvg.font_size(LABEL_FONT_SIZE);
+        nvg.begin_path();
+        nvg.fill_color(color);
+        if output {
+            nvg.text_align(RIGHT|BASELINE);
+            nvg.text(x+w-PAD_RIGHT as f32, y+h-TEXT_PAD_DOWN as f32, label);
+        } else {
+            nvg.text_align(LEFT|BASELINE);
+            nvg.text(x+PAD_LEFT as f32, y+h-TEXT_PAD_DOWN as f32, label);
+        }
+    }
+
+    /// Fill (x, y, w, h) with the background and rule it with lines 'step'
+    /// apart, one of them through (ox, oy)
+    fn draw_canvas_grid(&mut self, x:f32,y:f32, w:f32,h:f32, ox:f32,oy:f32, step:f32)
+    {
+        self.draw_background(x, y, w, h);
+        let color = offset_color(self.theme().backgroundColor, CANVAS_GRID_SHADE);
+        let nvg = self.nvg();
+        nvg.begin_path();
+        let mut gx = x + (ox - x) - ((ox - x) / step).floor() * step;
+        while gx < x + w {
+            nvg.move_to(gx.floor() + 0.5, y);
+            nvg.line_to(gx.floor() + 0.5, y + h);
+            gx += step;
+        }
+        let mut gy = y + (oy - y) - ((oy - y) / step).floor() * step;
+        while gy < y + h {
+            nvg.move_to(x, gy.floor() + 0.5);
+            nvg.line_to(x + w, gy.floor() + 0.5);
+            gy += step;
+        }
+        nvg.stroke_color(color);
+        nvg.stroke_width(1.0);
+        nvg.stroke();
+    }
 }
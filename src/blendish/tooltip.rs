@@ -0,0 +1,68 @@
+use blendish::*;
+use blendish::themed_draw::ThemedDraw;
+use blendish::widget::Widget;
+use oui::*;
+use oui::geom::{Vec2, Rect};
+
+/// where a w x h tooltip for the cursor at 'at' goes: below the cursor,
+/// or above it if there's no room below, and moved as far as needed to
+/// stay inside 'screen'
+pub fn tooltip_rect(at: Vec2, w: f32, h: f32, screen: Rect) -> Rect {
+    let right = screen.x + screen.w;
+    let bottom = screen.y + screen.h;
+    let mut y = at.y + TOOLTIP_OFFSET;
+    if y + h > bottom && at.y - TOOLTIP_OFFSET - h >= screen.y {
+        y = at.y - TOOLTIP_OFFSET - h;
+    }
+    let x = max(screen.x, min(at.x, right - w));
+    let y = max(screen.y, min(y, bottom - h));
+    Rect { x: x, y: y, w: w, h: h }
+}
+
+/// the size 'tip' needs: a line for its text and shortcut, and one for its
+/// description, if any
+pub fn tooltip_size(vg: &mut ThemedContext, tip: &Tooltip) -> (f32, f32) {
+    let font = *vg.font();
+    let mut w = label_width(vg.nvg(), -1, tip.text.as_slice(), &font);
+    let mut h = WIDGET_HEIGHT as f32;
+    if tip.shortcut.len() > 0 {
+        w += TOOLTIP_SHORTCUT_GAP
+            + label_width(vg.nvg(), -1, tip.shortcut.as_slice(), &font) - PAD_LEFT as f32;
+    }
+    if tip.description.len() > 0 {
+        w = max(w, label_width(vg.nvg(), -1, tip.description.as_slice(), &font));
+        h += WIDGET_HEIGHT as f32;
+    }
+    (w, h)
+}
+
+/// draw the tooltip that's showing, if any, inside a window of w x h;
+/// call after draw_ui, so it's on top
+pub fn draw_tooltip(ui: &mut Context<Widget>, vg: &mut ThemedContext, w: f32, h: f32) {
+    let item = ui.get_tooltip_item();
+    if item.invalid() { return; }
+    let tip = match ui.get_tooltip(item) {
+        Some(tip) => tip.clone(),
+        None => return
+    };
+    let (tw, th) = tooltip_size(vg, &tip);
+    let screen = Rect { x: 0.0, y: 0.0, w: w, h: h };
+    let rect = tooltip_rect(ui.get_tooltip_pos(), tw, th, screen);
+    vg.draw_tooltip(rect.x, rect.y, rect.w, rect.h,
+        tip.text.as_slice(), tip.shortcut.as_slice(), tip.description.as_slice());
+}
+
+#[test]
+fn test_tooltip_stays_in_the_window() {
+    let screen = Rect { x: 0.0, y: 0.0, w: 600.0, h: 480.0 };
+    // below the cursor when there's room
+    let r = tooltip_rect(Vec2 { x: 100.0, y: 100.0 }, 120.0, 42.0, screen);
+    assert_eq!(r, Rect { x: 100.0, y: 100.0 + TOOLTIP_OFFSET, w: 120.0, h: 42.0 });
+    // above it near the bottom, and pushed left near the right edge
+    let r = tooltip_rect(Vec2 { x: 550.0, y: 470.0 }, 120.0, 42.0, screen);
+    assert_eq!(r, Rect { x: 480.0, y: 470.0 - TOOLTIP_OFFSET - 42.0, w: 120.0, h: 42.0 });
+    // too big: pinned to the top left
+    let r = tooltip_rect(Vec2 { x: 300.0, y: 10.0 }, 700.0, 500.0, screen);
+    assert_eq!(r.x, 0.0);
+    assert_eq!(r.y, 0.0);
+}
@@ -1,6 +1,4 @@
 
-use std::rc::Rc;
-use std::cell::Cell;  // shared refs to ui-updatable data
 
 pub use {
     ItemState,
@@ -10,27 +8,111 @@ pub use {
 use blendish::*;
 use blendish::themed_draw::ThemedDraw;
 use blendish::lowlevel_draw::LowLevelDraw;
+use blendish::control::Control;
+use blendish::menu::{menu_state, option_label};
+use blendish::scroll::handle_size;
+use blendish::canvas::draw_canvas;
+use blendish::node::{NodeGraph, NodeEdit, draw_node_graph};
+use blendish::textedit::{TextEdit, caret_at, COMMIT, CANCEL};
 use draw::corners::corner_flags;
+use draw::iconsheet::no_icon;
 use oui::*;
+use oui::geom::{Vec2, Rect};
 
 
 pub enum Widget {
     Label { iconid:i32, text:String },
     Button { iconid:i32, text:String },
-    Check { text:String, option: Rc<Cell<bool>> },
-    Radio { iconid:i32, text:String, index: Rc<Cell<i32>> },
-    Slider { text:String, progress: Rc<Cell<f32>> },
+    Check { text:String, option: Property<bool> },
+    Radio { iconid:i32, text:String, index: Property<i32> },
+    Slider { text:String, progress: Property<f32> },
+    // 'edit' is there while the field has the focus; 'carets' are where
+    // draw_ui last put them, for placing the caret with the mouse
+    TextField { iconid:i32, text: Property<String>, edit: Option<TextEdit>,
+        carets: Vec<(uint, f32)> },
+    // 'start' is the value when the button went down, 'last_click' the
+    // time of the last click, to tell double-clicks; 'edit' and 'carets'
+    // are as for TextField, while typing in a value
+    NumberField { text:String, value: Property<f32>, spec: NumberSpec,
+        start: f32, last_click: f64, edit: Option<TextEdit>, carets: Vec<(uint, f32)> },
+    // a combo box: shows options[index], and opens 'menu' to choose
+    Choice { options: Vec<(i32, String)>, index: Property<i32>, menu: Item },
+    // a popup menu, opened by 'owner'; 'highlight' is the child id the
+    // keyboard is on, or -1
+    Menu { owner: Item, highlight: i32 },
+    // 'index' counts the menu's options, not its children, and is -1 for
+    // commands and submenus; 'handler' runs when a command is chosen
+    MenuItem { iconid:i32, text:String, index: i32, shortcut: String,
+        check: Option<Property<bool>>, submenu: Item, handler: Handler<Widget> },
+    MenuLabel { iconid:i32, text:String },
+    MenuSeparator { unused:i8 },
+    MenuBar { unused:i8 },
+    // a menu bar's entry, opening 'menu'
+    MenuTitle { text:String, menu: Item },
+    // 'offset' is how far the handle is along the track, 0..1; its size
+    // comes from 'panel', if valid, else 'size'. 'grab' is the offset
+    // when the handle was grabbed, or -1
+    ScrollBar { offset: Property<f32>, size: f32, panel: Item, grab: f32 },
+    // shows part of its content, clipped; see blendish::scroll
+    ScrollPanel { offset: Property<f32> },
+    // a node graph; 'selected' is the node last pressed, 'start' the
+    // graph when the press began. see blendish::node
+    NodeEditor { graph: Property<NodeGraph>, selected: Option<uint>, edit: NodeEdit,
+        start: NodeGraph },
+    // an infinite canvas, its children in canvas points; see blendish::canvas
+    Canvas { unused:i8 },
     Row { pub unused:i8 /*compiler doesn't support empty struct variants*/},
     Column { pub unused:i8 },
-    Panel { unused:i8 }
+    Panel { unused:i8 },
+    // anything else; see blendish::control
+    Custom { control: Box<Control+'static> }
+}
+
+
+/// the values a NumberField takes, and how dragging changes them
+pub struct NumberSpec {
+    pub min: f32,
+    pub max: f32,
+    // arrow clicks add or subtract this; ctrl-drags snap to it
+    pub step: f32,
+    // digits after the decimal point
+    pub precision: uint,
+    // value change per pixel dragged; shift makes it 10 times finer
+    pub speed: f32,
+}
+
+impl NumberSpec {
+    /// a spec that drags one step per 4 pixels
+    pub fn new(min: f32, max: f32, step: f32, precision: uint) -> NumberSpec {
+        NumberSpec { min: min, max: max, step: step, precision: precision, speed: step / 4.0 }
+    }
+
+    /// 'v' made valid: within min..max, at the given precision
+    pub fn fit(&self, v: f32) -> f32 {
+        let scale = 10.0f32.powi(self.precision as i32);
+        clamp((v * scale).round() / scale, self.min, self.max)
+    }
+
+    /// 'v' rounded to a multiple of step
+    pub fn snap(&self, v: f32) -> f32 {
+        if self.step <= 0.0 { v } else { (v / self.step).round() * self.step }
+    }
+
+    pub fn format(&self, v: f32) -> String {
+        ::std::f32::to_str_exact(v, self.precision)
+    }
 }
 
 
 // draw item and recurse for its children
-pub fn draw_ui(ui: &mut Context<Widget>, vg: &mut ThemedContext, item: Item, x: i32, y: i32) {
-    let (x,y,w,h) = {
-        let rect = ui.get_rect(item);
-        ((rect.x + x) as f32, (rect.y + y) as f32, rect.w as f32, rect.h as f32)
+pub fn draw_ui(ui: &mut Context<Widget>, vg: &mut ThemedContext, item: Item, x: f32, y: f32) {
+    let (x,y,w,h) = match ui.get_display_rect(item) {
+        // in transition: draw it on its way, children follow along
+        Some(rect) => (rect.x, rect.y, rect.w, rect.h),
+        None => {
+            let rect = ui.get_rect(item);
+            (rect.x + x, rect.y + y, rect.w, rect.h)
+        }
     };
 
     // OUI extends state, adding a "frozen" which gets dimmed
@@ -40,6 +122,7 @@ pub fn draw_ui(ui: &mut Context<Widget>, vg: &mut ThemedContext, item: Item, x:
         ACTIVE => (ACTIVE, false),
         _ => (COLD, true)
     };
+    let item_state = menu_state(ui, item, item_state);
     if frozen {
         vg.nvg().global_alpha(DISABLED_ALPHA);
     }
@@ -47,6 +130,11 @@ pub fn draw_ui(ui: &mut Context<Widget>, vg: &mut ThemedContext, item: Item, x:
     let kidid = ui.get_child_id(item);
 
     let cornerflags = corner_flags(ui, item);
+    let handle = handle_size(ui, item);
+    // in the coordinates the item is drawn in, which a canvas may zoom
+    let cursor = ui.get_cursor();
+    let cursor = ui.to_item_space(item, cursor);
+    let view = ui.get_canvas_view(item);
 
     match *ui.get_widget(item) {
         Panel { unused:_ } => {
@@ -81,20 +169,166 @@ pub fn draw_ui(ui: &mut Context<Widget>, vg: &mut ThemedContext, item: Item, x:
                 cornerflags, item_state,
                 val, label.as_slice(), val_str.as_slice());
         }
+        TextField { iconid:iconid, text:ref text, edit:ref edit, carets:ref mut carets } => {
+            let (shown, state, cbegin, cend) = match *edit {
+                Some(ref e) => {
+                    let (cbegin, cend) = e.selection();
+                    (e.text.clone(), ACTIVE, cbegin, cend)
+                }
+                None => (text.get(), item_state, 0, 0)
+            };
+            let font = vg.font();
+            *carets = vg.nvg().text_caret_positions(iconid as u32, &*font,
+                LABEL_FONT_SIZE, shown.as_slice());
+            vg.draw_text_field(x, y, w, h,
+                cornerflags, state,
+                iconid as u32, shown.as_slice(), cbegin, cend);
+        }
+        NumberField { text:ref label, value:ref value, spec:ref spec,
+            start:_, last_click:_, edit:ref edit, carets:ref mut carets
+        } => {
+            match *edit {
+                Some(ref e) => {
+                    // typing in a value: drawn as a text field
+                    let (cbegin, cend) = e.selection();
+                    let font = vg.font();
+                    *carets = vg.nvg().text_caret_positions(no_icon() as u32, &*font,
+                        LABEL_FONT_SIZE, e.text.as_slice());
+                    vg.draw_text_field(x, y, w, h,
+                        cornerflags, ACTIVE,
+                        no_icon() as u32, e.text.as_slice(), cbegin, cend);
+                }
+                None => {
+                    let val_str = spec.format(value.get());
+                    vg.draw_number_field(x, y, w, h,
+                        cornerflags, item_state,
+                        label.as_slice(), val_str.as_slice());
+                }
+            }
+        }
+        Choice { options:ref options, index:ref index, menu:_ } => {
+            let (iconid, label) = option_label(options.as_slice(), index.get());
+            vg.draw_choice_button(x, y, w, h,
+                cornerflags, item_state,
+                iconid as u32, label);
+        }
+        Menu { owner:_, highlight:_ } => {
+            vg.draw_menu_background(x, y, w, h, cornerflags);
+        }
+        MenuItem { iconid:iconid, text:ref label, index:_, shortcut:ref shortcut,
+            check:ref check, submenu:submenu, handler:_ } => {
+            let mut state = item_state;
+            match *check {
+                Some(ref option) =>
+                    vg.draw_menu_check(x, y, w, h, &mut state, option.get(), label.as_slice()),
+                None =>
+                    vg.draw_menu_item(x, y, w, h, &mut state, iconid as u32, label.as_slice()),
+            }
+            if submenu.valid() {
+                vg.draw_submenu_arrow(x, y, w, h, state);
+            } else if !shortcut.is_empty() {
+                vg.draw_menu_shortcut(x, y, w, h, state, shortcut.as_slice());
+            }
+        }
+        MenuLabel { iconid:iconid, text:ref label } => {
+            vg.draw_menu_label(x, y, w, h, iconid as u32, label.as_slice());
+        }
+        MenuSeparator { unused:_ } => {
+            vg.draw_menu_separator(x, y, w, h);
+        }
+        MenuBar { unused:_ } => {
+            vg.draw_background(x, y, w, h);
+        }
+        MenuTitle { text:ref label, menu:_ } => {
+            let mut state = item_state;
+            vg.draw_menu_item(x, y, w, h, &mut state, no_icon() as u32, label.as_slice());
+        }
+        ScrollBar { offset:ref offset, size:_, panel:_, grab:_ } => {
+            // the handle lights up under the cursor, too
+            let state = if item_state == COLD { COLD } else { ACTIVE };
+            vg.draw_scrollbar(x, y, w, h, state, offset.get(), handle);
+        }
+        NodeEditor { graph:ref graph, selected:selected, edit:ref edit, start:_ } => {
+            let at = Vec2 { x: cursor.x - x, y: cursor.y - y };
+            draw_node_graph(vg, &graph.get(), x, y, w, h, item_state, selected, edit, at);
+        }
+        Canvas { unused:_ } => {
+            match view {
+                Some(ref view) => draw_canvas(vg, x, y, w, h, view),
+                None => vg.draw_background(x, y, w, h)
+            }
+        }
+        Custom { control:ref control } => {
+            control.draw(vg, Rect { x: x, y: y, w: w, h: h }, item_state);
+        }
         _ => {}
     }
 
+    let clip = match *ui.get_widget(item) {
+        ScrollPanel { offset:_ } => true,
+        _ => view.is_some()
+    };
+    if clip {
+        // within whatever clips the item already, e.g. an outer panel
+        vg.nvg().save();
+        vg.nvg().intersect_scissor(x, y, w, h);
+    }
+    match view {
+        // the children are drawn from (x, y) in canvas points, like
+        // get_abs_rect has them; the view takes them from there
+        Some(view) => {
+            vg.nvg().translate(x + view.pan.x, y + view.pan.y);
+            vg.nvg().scale(view.zoom, view.zoom);
+            vg.nvg().translate(-x, -y);
+        }
+        None => {}
+    }
+
     let mut kid = ui.first_child(item);
     while kid.valid() { // was, > 0 meaning valid and not root ?
-        draw_ui(ui, vg, kid, x as i32, y as i32);
+        draw_ui(ui, vg, kid, x, y);
         kid = ui.next_sibling(kid);
     }
 
+    if clip {
+        vg.nvg().restore();
+    }
+
     if frozen {
         vg.nvg().global_alpha(1.0);  // this item was frozen: restore full alpha
     }
 }
 
+// while a drag is in progress, draw a translucent copy of the source item
+// under the cursor; call after draw_ui, so it floats above everything else.
+pub fn draw_drag_preview(ui: &mut Context<Widget>, vg: &mut ThemedContext) {
+    let source = ui.get_drag_source();
+    if source.invalid() { return; }
+    let parent = ui.parent(source);
+    let origin = if parent.valid() { ui.get_abs_rect(parent) } else { Rect::zero() };
+    let delta = ui.get_cursor_start_delta();
+    vg.nvg().global_alpha(TRANSPARENT_ALPHA);
+    draw_ui(ui, vg, source, origin.x + delta.x, origin.y + delta.y);
+    vg.nvg().global_alpha(1.0);
+}
+
+// the state an item is drawn in: oui's state, except that checked checks,
+// selected radios and choices with their menu open show as ACTIVE (see
+// draw_ui), and anything in a frozen container is FROZEN
+pub fn get_widget_state(ui: &mut Context<Widget>, item: Item) -> ItemState {
+    if ui.is_frozen(item) { return FROZEN; }
+    let state = ui.get_state(item);
+    let state = menu_state(ui, item, state);
+    let kidid = ui.get_child_id(item);
+    match *ui.get_widget(item) {
+        Check { text:_, option:ref option } if option.get() => ACTIVE,
+        Radio { iconid:_, text:_, index:ref index } if index.get() == kidid => ACTIVE,
+        TextField { iconid:_, text:_, edit:Some(_), carets:_ } => ACTIVE,
+        NumberField { text:_, value:_, spec:_, start:_, last_click:_, edit:Some(_), carets:_ } => ACTIVE,
+        _ => state
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////
 // widget constructors
 
@@ -126,9 +360,11 @@ pub fn button(ui:&mut Context<Widget>, parent: Item, tag: Tag, iconid: i32, labe
 }
 
 pub fn check(ui:&mut Context<Widget>, parent: Item, tag: Tag, label: &str,
-    option: Rc<Cell<bool>>, handler: Handler<Widget>)
+    option: Property<bool>, handler: Handler<Widget>)
 -> Item
 {
+    // redraw when the app changes the option
+    ui.watch(&option);
     let chk = Check { text:label.to_string(), option:option };
     let item = ui.item(chk);
     ui.set_tag(item, tag);
@@ -139,23 +375,25 @@ pub fn check(ui:&mut Context<Widget>, parent: Item, tag: Tag, label: &str,
 }
 
 pub fn slider(ui:&mut Context<Widget>, parent: Item, tag: Tag, label: &str,
-    progress: Rc<Cell<f32>>)
+    progress: Property<f32>)
 -> Item
 {
+    ui.watch(&progress);
     let sli = Slider { text:label.to_string(), progress:progress };
     let item = ui.item(sli);
     ui.set_tag(item, tag);
     ui.set_size(item, 0, WIDGET_HEIGHT);
-    // attach our slider event handler and capture two classes of events
-    ui.set_handler(item, Some(sliderhandler), BUTTON0_DOWN|BUTTON0_CAPTURE);
+    // attach our slider event handler and capture three classes of events
+    ui.set_handler(item, Some(sliderhandler), BUTTON0_DOWN|BUTTON0_CAPTURE|BUTTON0_UP);
     ui.append(parent, item);
     return item;
 }
 
 pub fn radio(ui:&mut Context<Widget>, parent: Item, tag: Tag, iconid: i32, label: &str,
-    index: Rc<Cell<i32>>)
+    index: Property<i32>)
 -> Item
 {
+    ui.watch(&index);
     let rad = Radio { iconid:iconid, text:label.to_string(), index:index };
     let item = ui.item(rad);
     ui.set_tag(item, tag);
@@ -166,6 +404,37 @@ pub fn radio(ui:&mut Context<Widget>, parent: Item, tag: Tag, iconid: i32, label
     return item;
 }
 
+pub fn textfield(ui:&mut Context<Widget>, parent: Item, tag: Tag, iconid: i32,
+    text: Property<String>)
+-> Item
+{
+    ui.watch(&text);
+    let field = TextField { iconid:iconid, text:text, edit:None, carets:Vec::new() };
+    let item = ui.item(field);
+    ui.set_tag(item, tag);
+    ui.set_size(item, 0, WIDGET_HEIGHT);
+    ui.set_handler(item, Some(textfieldhandler),
+        BUTTON0_DOWN|BUTTON0_CAPTURE|KEY_DOWN|CHAR|BLUR);
+    ui.append(parent, item);
+    return item;
+}
+
+pub fn numberfield(ui:&mut Context<Widget>, parent: Item, tag: Tag, label: &str,
+    value: Property<f32>, spec: NumberSpec)
+-> Item
+{
+    ui.watch(&value);
+    let num = NumberField { text:label.to_string(), value:value, spec:spec,
+        start:0.0, last_click:-1.0, edit:None, carets:Vec::new() };
+    let item = ui.item(num);
+    ui.set_tag(item, tag);
+    ui.set_size(item, 0, WIDGET_HEIGHT);
+    ui.set_handler(item, Some(numberfieldhandler),
+        BUTTON0_DOWN|BUTTON0_CAPTURE|BUTTON0_UP|KEY_DOWN|CHAR|BLUR);
+    ui.append(parent, item);
+    return item;
+}
+
 pub fn panel(ui:&mut Context<Widget>) -> Item
 {
     ui.item(Panel{unused:0})
@@ -220,56 +489,45 @@ pub fn demohandler(ui: &mut Context<Widget>, item: Item, _event: EventFlags) {
 }
 pub fn checkhandler(ui: &mut Context<Widget>, item: Item, _event: EventFlags) {
     let tag = ui.get_tag(item);
-    let widget = ui.get_widget(item);
-    match *widget {
-        Check { text: ref mut label, option: ref option } => {
-            println!("clicked: #{} '{}'", tag, label);
-            let ref cell: Rc<Cell<bool>> = *option;
-            cell.set(!cell.get());
-        }
-        _ => {}
-    }
-}
-pub fn freezehandler(ui: &mut Context<Widget>, item:Item, event:EventFlags) {
-    // "super" call, handles default cell-update-on-click
-    checkhandler(ui, item, event);
-    // we didn't cache our target anywhere, so go find it.
-    // (demo freezes the right column of the row that's above the
-    // button that's above the "Freeze" checkbox)
-    let tgt = {
-        let it = ui.prev_sibling(item);
-        let it = ui.prev_sibling(it);
-        let tgt = ui.last_child(it);
-        tgt
-    };
-
-    let tgt_id = ui.get_child_id(tgt);
-    let friz = {
+    let toggled = {
         let widget = ui.get_widget(item);
         match *widget {
-            Check { text:_, option: ref option } => {
-                option.get()
+            Check { text: ref mut label, option: ref option } => {
+                println!("clicked: #{} '{}'", tag, label);
+                let before = option.get();
+                if option.set(!before) { Some(SetProperty::new(option.clone(), before, !before)) }
+                else { None }
             }
-            _ => { false }
+            _ => None
         }
     };
-
-    println!("freezing: #{} to '{}'", tgt_id, friz);
-    ui.set_frozen(tgt, friz);
+    match toggled {
+        Some(cmd) => ui.push_command(box cmd),
+        None => {}
+    }
+    ui.request_redraw();
 }
 // simple logic for a radio button
 pub fn radiohandler(ui: &mut Context<Widget>, item: Item, _event: EventFlags) {
     let tag = ui.get_tag(item);
     let kidid = ui.get_child_id(item);
-    let widget = ui.get_widget(item);
-    match *widget {
-        Radio { iconid:_, text: ref mut label, index: ref mut index } => {
-            println!("clicked: #{} '{}'", tag, label);
-            let ref mut cell: Rc<Cell<i32>> = *index;
-            cell.set(kidid);
+    let selected = {
+        let widget = ui.get_widget(item);
+        match *widget {
+            Radio { iconid:_, text: ref mut label, index: ref index } => {
+                println!("clicked: #{} '{}'", tag, label);
+                let before = index.get();
+                if index.set(kidid) { Some(SetProperty::new(index.clone(), before, kidid)) }
+                else { None }
+            }
+            _ => None
         }
-        _ => {}
+    };
+    match selected {
+        Some(cmd) => ui.push_command(box cmd),
+        None => {}
     }
+    ui.request_redraw();
 }
 
 // simple logic for a slider
@@ -281,29 +539,272 @@ pub fn sliderhandler(ui: &mut Context<Widget>, item: Item, event: EventFlags) {
     static mut sliderstart: f32 = 0.0;
     let pos = ui.get_cursor_start_delta();
     let rc = ui.get_rect(item);
-    let widget = ui.get_widget(item);
-    match event {
-        BUTTON0_DOWN => {
-            println!("button0 down");
-            match *widget {
-                Slider { text:_, progress: ref currval } => {
-                    unsafe { sliderstart = currval.get() };
+    let mut dragged = None;
+    let changed = {
+        let widget = ui.get_widget(item);
+        match event {
+            BUTTON0_DOWN => {
+                println!("button0 down");
+                match *widget {
+                    Slider { text:_, progress: ref currval } => {
+                        unsafe { sliderstart = currval.get() };
+                    }
+                    _ => {}
+                }
+                false
+            }
+            BUTTON0_CAPTURE => {
+                println!("button0 capture");
+                let val = unsafe { sliderstart + (pos.x / rc.w) };
+                let val = clamp(val, 0.0, 1.0);
+                match *widget {
+                    Slider { text:_, progress: ref currval } => {
+                        currval.set(val)
+                    }
+                    _ => false
+                }
+            }
+            BUTTON0_UP => {
+                // the whole drag is one undo step
+                match *widget {
+                    Slider { text:_, progress: ref currval } => {
+                        let start = unsafe { sliderstart };
+                        if currval.get() != start {
+                            dragged = Some(SetProperty::new(currval.clone(), start, currval.get()));
+                        }
+                    }
+                    _ => {}
+                }
+                false
+            }
+            _ => { println!("missed a slider event: {}", event); false }
+        }
+    };
+    match dragged {
+        Some(cmd) => ui.push_command(box cmd),
+        None => {}
+    }
+    if changed {
+        ui.request_redraw();
+    }
+}
+
+// editing text: click to focus and place the caret, drag or shift-click
+// to select, Enter (or clicking elsewhere) to keep the text, Escape to
+// drop the changes
+pub fn textfieldhandler(ui: &mut Context<Widget>, item: Item, event: EventFlags) {
+    let rect = ui.get_abs_rect(item);
+    let x = ui.get_cursor().x - rect.x;
+    let shift = ui.get_modifiers().contains(MOD_SHIFT);
+    let key = ui.get_key();
+    let mods = ui.get_modifiers();
+    let ch = ui.get_char();
+    if event == BUTTON0_DOWN {
+        ui.set_focus(item);
+    }
+
+    // the edit to finish, if any, and whether to keep it
+    let mut finish = None;
+    {
+        let widget = ui.get_widget(item);
+        match *widget {
+            TextField { iconid:_, text:ref text, edit:ref mut edit, carets:ref carets } => {
+                match event {
+                    BUTTON0_DOWN | BUTTON0_CAPTURE => {
+                        if edit.is_none() {
+                            *edit = Some(TextEdit::new(text.get().as_slice()));
+                        }
+                        let e = edit.as_mut().unwrap();
+                        let pos = caret_at(carets.as_slice(), x);
+                        // a click starts a new selection, unless shifted;
+                        // dragging extends it
+                        e.move_to(pos, shift || event == BUTTON0_CAPTURE);
+                    }
+                    KEY_DOWN => match (edit.as_mut(), key) {
+                        (Some(e), Some(key)) => match e.key(key, mods) {
+                            COMMIT => finish = Some(true),
+                            CANCEL => finish = Some(false),
+                            _ => {}
+                        },
+                        _ => {}
+                    },
+                    CHAR => match (edit.as_mut(), ch) {
+                        (Some(e), Some(c)) => e.insert(c),
+                        _ => {}
+                    },
+                    BLUR => finish = Some(true),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match finish {
+        Some(keep) => finish_text_edit(ui, item, keep),
+        None => {}
+    }
+    ui.request_redraw();
+}
+
+// end editing; if 'keep', the text goes to the property, as one undo step
+fn finish_text_edit(ui: &mut Context<Widget>, item: Item, keep: bool) {
+    let change = {
+        let widget = ui.get_widget(item);
+        match *widget {
+            TextField { iconid:_, text:ref text, edit:ref mut edit, carets:_ } => {
+                match edit.take() {
+                    Some(e) => {
+                        let before = text.get();
+                        if keep && text.set(e.text.clone()) {
+                            Some(SetProperty::new(text.clone(), before, e.text))
+                        } else { None }
+                    }
+                    None => None
+                }
+            }
+            _ => None
+        }
+    };
+    match change {
+        Some(cmd) => ui.push_command(box cmd),
+        None => {}
+    }
+    if ui.get_focus() == item {
+        ui.set_focus(Item::none());
+    }
+}
+
+// a number field: drag to change the value (shift: finer, ctrl: in steps),
+// click an arrow to step, double-click to type a value
+pub fn numberfieldhandler(ui: &mut Context<Widget>, item: Item, event: EventFlags) {
+    let rect = ui.get_abs_rect(item);
+    let x = ui.get_cursor().x - rect.x;
+    let dx = ui.get_cursor_start_delta().x;
+    let mods = ui.get_modifiers();
+    let key = ui.get_key();
+    let ch = ui.get_char();
+    let now = ui.get_time();
+    let dragged = dx.abs() >= NUMBER_DRAG_THRESHOLD;
+
+    // what to do after the widget borrow: record a change, start or
+    // finish typing
+    let mut change = None;
+    let mut start_typing = false;
+    let mut finish = None;
+    {
+        let widget = ui.get_widget(item);
+        match *widget {
+            NumberField { text:_, value:ref value, spec:ref spec,
+                start:ref mut start, last_click:ref mut last_click,
+                edit:ref mut edit, carets:ref carets
+            } => {
+                match (event, edit.as_mut()) {
+                    // typing: the mouse moves the caret, as in a TextField
+                    (BUTTON0_DOWN, Some(e)) | (BUTTON0_CAPTURE, Some(e)) => {
+                        let pos = caret_at(carets.as_slice(), x);
+                        e.move_to(pos, mods.contains(MOD_SHIFT) || event == BUTTON0_CAPTURE);
+                    }
+                    (BUTTON0_UP, Some(_)) => {}
+                    (KEY_DOWN, Some(e)) => match key {
+                        Some(key) => match e.key(key, mods) {
+                            COMMIT => finish = Some(true),
+                            CANCEL => finish = Some(false),
+                            _ => {}
+                        },
+                        None => {}
+                    },
+                    (CHAR, Some(e)) => match ch {
+                        Some(c) => e.insert(c),
+                        None => {}
+                    },
+                    (BLUR, Some(_)) => finish = Some(true),
+
+                    (BUTTON0_DOWN, None) => {
+                        *start = value.get();
+                    }
+                    (BUTTON0_CAPTURE, None) => {
+                        if dragged {
+                            let mut delta = dx * spec.speed;
+                            if mods.contains(MOD_SHIFT) { delta *= 0.1; }
+                            let mut v = *start + delta;
+                            if mods.contains(MOD_CTRL) { v = spec.snap(v); }
+                            value.set(spec.fit(v));
+                        }
+                    }
+                    (BUTTON0_UP, None) => {
+                        if !dragged {
+                            // a click: on an arrow, or maybe the second of
+                            // a double-click
+                            if x < NUMBER_ARROW_ZONE {
+                                value.set(spec.fit(*start - spec.step));
+                            } else if x > rect.w - NUMBER_ARROW_ZONE {
+                                value.set(spec.fit(*start + spec.step));
+                            } else if now - *last_click <= DOUBLE_CLICK_TIME {
+                                *edit = Some(TextEdit::new(spec.format(value.get()).as_slice()));
+                                *last_click = -1.0;
+                                start_typing = true;
+                            } else {
+                                *last_click = now;
+                            }
+                        }
+                        // the whole press is one undo step
+                        if value.get() != *start {
+                            change = Some(SetProperty::new(value.clone(), *start, value.get()));
+                        }
+                    }
+                    _ => {}
                 }
-                _ => {}
             }
+            _ => {}
         }
-        BUTTON0_CAPTURE => {
-            println!("button0 capture");
-            let val = unsafe { sliderstart + (pos.x as f32 / rc.w as f32) };
-            let val = clamp(val, 0.0, 1.0);
-            match *widget {
-                Slider { text:_, progress: ref currval } => {
-                    currval.set(val);
+    }
+
+    match change {
+        Some(cmd) => ui.push_command(box cmd),
+        None => {}
+    }
+    if start_typing {
+        ui.set_focus(item);
+    }
+    match finish {
+        Some(keep) => finish_number_edit(ui, item, keep),
+        None => {}
+    }
+    ui.request_redraw();
+}
+
+// stop typing; if 'keep' and the text is a number, it's the new value
+fn finish_number_edit(ui: &mut Context<Widget>, item: Item, keep: bool) {
+    let change = {
+        let widget = ui.get_widget(item);
+        match *widget {
+            NumberField { text:_, value:ref value, spec:ref spec,
+                start:_, last_click:_, edit:ref mut edit, carets:_
+            } => {
+                let typed = match edit.take() {
+                    Some(e) => if keep { from_str::<f32>(e.text.as_slice().trim()) } else { None },
+                    None => None
+                };
+                match typed {
+                    Some(v) => {
+                        let before = value.get();
+                        if value.set(spec.fit(v)) {
+                            Some(SetProperty::new(value.clone(), before, value.get()))
+                        } else { None }
+                    }
+                    None => None
                 }
-                _ => {}
             }
+            _ => None
         }
-        _ => { println!("missed a slider event: {}", event) }
+    };
+    match change {
+        Some(cmd) => ui.push_command(box cmd),
+        None => {}
+    }
+    if ui.get_focus() == item {
+        ui.set_focus(Item::none());
     }
 }
 
@@ -353,3 +854,142 @@ pub fn hgrouphandler(ui: &mut Context<Widget>, parent: Item, _event: EventFlags)
 }
 // end handlers
 ///////////////////////////////////////////////////////////////////////
+
+// a drag-and-drop test: item 1 drags the string "cube", item 2 takes it,
+// item 3 won't. each label's text logs the drag events it got.
+#[cfg(test)]
+fn draghandler(ui: &mut Context<Widget>, item: Item, event: EventFlags) {
+    use std::any::AnyRefExt;
+
+    let tag = ui.get_tag(item);
+    let mut what = if event == DRAG_START {
+        ui.start_drag(box "cube".to_string());
+        "start".to_string()
+    } else if event == DRAG_END {
+        "end".to_string()
+    } else if event == DRAG_ENTER {
+        let wanted = ui.get_drag_payload()
+            .map_or(false, |p| p.downcast_ref::<String>().is_some());
+        ui.accept_drag(wanted && tag == 2);
+        "enter".to_string()
+    } else if event == DRAG_OVER {
+        "over".to_string()
+    } else if event == DRAG_LEAVE {
+        "leave".to_string()
+    } else {
+        let payload = ui.get_drag_payload()
+            .and_then(|p| p.downcast_ref::<String>())
+            .map_or(String::new(), |s| s.clone());
+        format!("drop {}", payload)
+    };
+    what.push_str(" ");
+    match *ui.get_widget(item) {
+        Label { iconid:_, text:ref mut text } => text.push_str(what.as_slice()),
+        _ => ()
+    }
+}
+
+#[cfg(test)]
+fn drag_ui(ui: &mut Context<Widget>) -> (Item, Item, Item) {
+    let root = panel(ui);
+    ui.set_size(root, 300, 100);
+    let mut items = Vec::new();
+    for &(tag, x, y) in [(1u64, 10i32, 10i32), (2, 150, 10), (3, 150, 60)].iter() {
+        let item = label(ui, root, -1, "");
+        ui.set_tag(item, tag);
+        ui.set_size(item, 80, WIDGET_HEIGHT);
+        ui.set_absolute(item, x, y);
+        let flags = if tag == 1 { DRAG_START|DRAG_END }
+            else { DRAG_ENTER|DRAG_OVER|DRAG_LEAVE|DROP };
+        ui.set_handler(item, Some(draghandler), flags);
+        items.push(item);
+    }
+    ui.layout();
+    (items[0], items[1], items[2])
+}
+
+#[cfg(test)]
+fn drag_log(ui: &mut Context<Widget>, item: Item) -> String {
+    match *ui.get_widget(item) {
+        Label { iconid:_, text:ref text } => text.clone(),
+        _ => fail!("not a label")
+    }
+}
+
+#[test]
+fn test_drop_onto_a_target_that_takes_it() {
+    use blendish::driver::Driver;
+
+    let mut ui: Context<Widget> = Context::create_context();
+    let (source, target, _) = drag_ui(&mut ui);
+    let mut driver = Driver::new(&mut ui);
+    let from = driver.center_of(source);
+    let to = driver.center_of(target);
+    driver.move_to(from.x, from.y);
+    driver.press();
+    // within the threshold, it's still a press
+    driver.move_to(from.x + 2.0, from.y);
+    assert!(!driver.ui.is_dragging());
+    driver.move_to(from.x + 10.0, from.y);
+    assert!(driver.ui.is_dragging());
+    assert_eq!(driver.ui.get_drag_source(), source);
+    driver.move_to(to.x, to.y);
+    assert_eq!(driver.ui.get_drag_target(), target);
+    assert!(driver.ui.is_drag_accepted());
+    driver.release();
+    assert!(!driver.ui.is_dragging());
+    assert_eq!(drag_log(&mut *driver.ui, target).as_slice(), "enter over drop cube ");
+    assert_eq!(drag_log(&mut *driver.ui, source).as_slice(), "start end ");
+}
+
+#[test]
+fn test_drop_onto_a_target_that_refuses_it() {
+    use blendish::driver::Driver;
+
+    let mut ui: Context<Widget> = Context::create_context();
+    let (source, taker, refuser) = drag_ui(&mut ui);
+    let mut driver = Driver::new(&mut ui);
+    let from = driver.center_of(source);
+    let over = driver.center_of(taker);
+    let to = driver.center_of(refuser);
+    driver.move_to(from.x, from.y);
+    driver.press();
+    driver.move_to(over.x, over.y);
+    // on to the next target: the first one is left behind
+    driver.move_to(to.x, to.y);
+    assert_eq!(driver.ui.get_drag_target(), refuser);
+    assert!(!driver.ui.is_drag_accepted());
+    driver.release();
+    assert_eq!(drag_log(&mut *driver.ui, taker).as_slice(), "enter over leave ");
+    assert_eq!(drag_log(&mut *driver.ui, refuser).as_slice(), "enter over leave ");
+    assert_eq!(drag_log(&mut *driver.ui, source).as_slice(), "start end ");
+}
+
+#[test]
+fn test_cancel_a_drag() {
+    use blendish::driver::Driver;
+
+    let mut ui: Context<Widget> = Context::create_context();
+    let (source, target, _) = drag_ui(&mut ui);
+    let mut driver = Driver::new(&mut ui);
+    let from = driver.center_of(source);
+    let to = driver.center_of(target);
+    driver.move_to(from.x, from.y);
+    driver.press();
+    driver.move_to(to.x, to.y);
+    driver.ui.cancel_drag();
+    assert!(!driver.ui.is_dragging());
+    assert_eq!(drag_log(&mut *driver.ui, target).as_slice(), "enter over leave ");
+    assert_eq!(drag_log(&mut *driver.ui, source).as_slice(), "start end ");
+    // the press goes on, but doesn't start another drag, nor drop
+    driver.move_to(to.x + 5.0, to.y);
+    assert!(!driver.ui.is_dragging());
+    driver.release();
+    assert_eq!(drag_log(&mut *driver.ui, target).as_slice(), "enter over leave ");
+    assert_eq!(drag_log(&mut *driver.ui, source).as_slice(), "start end ");
+    // the next press may drag again
+    driver.move_to(from.x, from.y);
+    driver.press();
+    driver.move_to(from.x + 10.0, from.y);
+    assert!(driver.ui.is_dragging());
+}
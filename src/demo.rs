@@ -7,19 +7,50 @@ extern crate gl;
 
 extern crate nanovg;
 extern crate nanoui;
+#[cfg(test)]
+extern crate serialize;
 
 use glfw::Context as GLFWContext;
-use std::rc::Rc;
-use std::cell::Cell;
+use std::io::timer::sleep;
+use std::time::Duration;
 
 use nanovg::{Ctx, Image, Font, ANTIALIAS,STENCIL_STROKES};
 use nanoui::blendish::theme::ThemedContext;
 use nanoui::blendish::themed_draw::ThemedDraw;
 use nanoui::blendish::widget::*;
+use nanoui::blendish::control::{Control, EventInfo, control, measure_controls};
+use nanoui::blendish::menu::{choice, menubar, menubar_menu, context_menu, MenuEntry, MenuCommand};
+use nanoui::blendish::menu::{MENU_OPTION, MENU_HEADING, MENU_SEPARATOR, MENU_COMMAND, MENU_SUBMENU};
+use nanoui::blendish::scroll::scrollpanel;
+use nanoui::blendish::tooltip::draw_tooltip;
+use nanoui::blendish::measure::{measure, FontMetrics, NanovgMetrics};
+#[cfg(test)]
+use nanoui::blendish::measure::FixedMetrics;
+use nanoui::draw::corners::CORNER_NONE;
+#[cfg(test)]
+use nanoui::blendish::driver::{Driver, Locator};
+#[cfg(test)]
+use nanoui::blendish::accessibility::{access_tree, export_json};
+#[cfg(test)]
+use serialize::json;
+#[cfg(test)]
+use nanoui::blendish::constants::{SUBMENU_DELAY, PAD_LEFT, PAD_RIGHT};
 use nanoui::oui::Context as OUIContext;
+use nanoui::oui::{Property, Item, EventFlags, Tooltip};
+use nanoui::oui::geom::Rect;
 use nanoui::oui::{LEFT,TOP,HFILL};
-
-use nanoui::draw::iconsheet::{icon_id, no_icon};
+use nanoui::oui::{
+    Key,
+        KEY_ARROW_LEFT, KEY_ARROW_RIGHT, KEY_ARROW_UP, KEY_ARROW_DOWN,
+        KEY_HOME, KEY_END, KEY_PAGE_UP, KEY_PAGE_DOWN,
+        KEY_BACKSPACE, KEY_DELETE, KEY_ENTER, KEY_ESCAPE, KEY_TAB, KEY_CODE,
+    KeyMods,
+        MOD_SHIFT, MOD_CTRL, MOD_ALT, MOD_SUPER,
+};
+
+use nanoui::draw::iconsheet::no_icon;
+use nanoui::draw::blender_icons::{ICON_GHOST, ICON_REC, ICON_PLAY, ICON_FILE_FOLDER,
+    ICON_MESH_CUBE, ICON_MESH_UVSPHERE, ICON_MESH_CYLINDER};
 
 mod macros;
 
@@ -56,22 +87,32 @@ impl Resources {
 #[deriving(Show)]
 pub struct AppData {
     // some persistent variables for demonstration
-    pub enum1:     Rc<Cell<i32>>,
-    pub progress1: Rc<Cell<f32>>,
-    pub progress2: Rc<Cell<f32>>,
-    pub option1:  Rc<Cell<bool>>,
-    pub option2:  Rc<Cell<bool>>,
-    pub option3:  Rc<Cell<bool>>,
+    pub enum1:     Property<i32>,
+    pub progress1: Property<f32>,
+    pub progress2: Property<f32>,
+    pub option1:   Property<bool>,
+    pub option2:   Property<bool>,
+    pub option3:   Property<bool>,
+    pub name:      Property<String>,
+    pub amount:    Property<f32>,
+    pub shape:     Property<i32>,
+    pub grid:      Property<bool>,
+    pub scroll:    Property<f32>,
 }
 pub fn init_app_data() -> AppData {
     // fake load-from-storage
     AppData {
-        enum1:     Rc::new(Cell::new(0)),
-        progress1: Rc::new(Cell::new(0.25)),
-        progress2: Rc::new(Cell::new(0.75)),
-        option1:   Rc::new(Cell::new(true)),
-        option2:   Rc::new(Cell::new(false)),
-        option3:   Rc::new(Cell::new(false)),
+        enum1:     Property::new(0),
+        progress1: Property::new(0.25),
+        progress2: Property::new(0.75),
+        option1:   Property::new(true),
+        option2:   Property::new(false),
+        option3:   Property::new(false),
+        name:      Property::new("Item 9".to_string()),
+        amount:    Property::new(2.5),
+        shape:     Property::new(0),
+        grid:      Property::new(true),
+        scroll:    Property::new(0.0),
     }
 }
 //#[unsafe_destructor]
@@ -84,7 +125,7 @@ pub fn init_app_data() -> AppData {
 ///////////////////////////////////////////////////////////////////////
 
 pub struct App<'a> {
-    mouse: (i32,i32),           // current mouse pos
+    mouse: (f32,f32),           // current mouse pos
     button: bool,               // is mousebutton pressed
     elapsed_time: f64,          // seconds since app start
     data: AppData,
@@ -103,7 +144,7 @@ impl<'a> App<'a> {
             ThemedContext::wrap(nvg, icons, font)
         };
         App {
-            mouse: (0,0),
+            mouse: (0.0,0.0),
             button: false,
             elapsed_time: 0.0,         // time since app start
             data: init_app_data(),
@@ -121,7 +162,9 @@ impl<'a> App<'a> {
 
     fn update(&mut self, dt: f64) {
         self.elapsed_time += dt;
-        update(&mut self.ui, self.mouse, self.button, self.elapsed_time as f32);
+        let font = *self.themed.font();
+        let metrics = NanovgMetrics::new(self.themed.nvg(), &font);
+        update(&mut self.ui, &metrics, self.mouse, self.button, self.elapsed_time);
     }
 
     fn render(&mut self, w:i32, h:i32, px_ratio: f32) {
@@ -150,7 +193,7 @@ fn main() {
     glfw.window_hint(glfw::OpenglForwardCompat(true));
     glfw.window_hint(glfw::OpenglProfile(glfw::OpenGlCoreProfile));
     glfw.window_hint(glfw::OpenglDebugContext(true));
-    let (window, events) = glfw.create_window(600,480, "nanoUI demo", glfw::Windowed)
+    let (window, events) = glfw.create_window(600,520, "nanoUI demo", glfw::Windowed)
         .expect("Failed to create GLFW window.");
 
     window.set_sticky_keys(true);
@@ -169,17 +212,35 @@ fn main() {
 
     while !window.should_close()
     {
+        // wait for window events; when the ui is idle, block until there
+        // are some, rather than spinning
+        match app.ui.next_wakeup() {
+            None => glfw.wait_events(),
+            Some(due) => {
+                // glfw can't wait with a timeout: nap until the ui wants to
+                // run, but only briefly, so that input stays responsive
+                let wait = (due - glfw.get_time()).min(1.0/60.0);
+                if wait > 0.0 {
+                    sleep(Duration::microseconds((wait*1e6) as i64));
+                }
+                glfw.poll_events();
+            }
+        }
+
         // get current timestamp and delta
         let t: f64 = glfw.get_time();
         let dt: f64 = t - prevt;
         prevt = t;
 
         // process outstanding window events
-        glfw.poll_events();
         for (_, event) in glfw::flush_messages(&events) {
             handle_window_event(&window, &mut app, (t, event));
         }
 
+        // update ui; skip drawing if nothing has changed
+        app.update(dt);
+        if !app.ui.needs_redraw() { continue; }
+
         let (win_width, win_height) = window.get_size();  // (i32,i32)
         let (fb_width, fb_height) = window.get_framebuffer_size();
         // Calculate pixel ration for hi-dpi devices.
@@ -196,14 +257,14 @@ fn main() {
         glcheck!(gl::Enable(gl::CULL_FACE));
         glcheck!(gl::Disable(gl::DEPTH_TEST));
 
-        // Update ui, and render to framebuffer
-        app.update(dt);
+        // render ui to framebuffer
         app.render(win_width, win_height, px_ratio);
 
         glcheck!(gl::Enable(gl::DEPTH_TEST));
 
         // swap in the freshened buffer
         window.swap_buffers();
+        app.ui.mark_drawn();
     }
 }
 
@@ -214,36 +275,55 @@ fn handle_window_event(
     (time, event): (f64, glfw::WindowEvent)
 ) {
     match event {
-        glfw::KeyEvent(glfw::KeyEscape, _, glfw::Press, _) => window.set_should_close(true),
+        // with a text field focused, Escape is for the field
+        glfw::KeyEvent(glfw::KeyEscape, _, glfw::Press, _) if app.ui.get_focus().invalid() =>
+            window.set_should_close(true),
+        glfw::MouseButtonEvent(glfw::MouseButtonRight, action, _) =>
+            app.ui.set_button(1/*right button*/, action == glfw::Press),
+        glfw::MouseButtonEvent(glfw::MouseButtonMiddle, action, _) =>
+            app.ui.set_button(2/*middle button*/, action == glfw::Press),
+        glfw::ScrollEvent(x, y)             => app.ui.set_wheel(x as f32, y as f32),
         glfw::MouseButtonEvent(_, glfw::Press, _) => app.button = true,
         glfw::MouseButtonEvent(_, glfw::Release, _) => app.button = false,
-        glfw::CursorPosEvent(xpos, ypos)    => app.mouse = (xpos as i32, ypos as i32),
+        glfw::CursorPosEvent(xpos, ypos)    => app.mouse = (xpos as f32, ypos as f32),
 
         glfw::PosEvent(x, y)                => println!("Time: {}, Window pos: ({}, {})", time, x, y),
-        glfw::SizeEvent(w, h)               => println!("Time: {}, Window size: ({}, {})", time, w, h),
+        glfw::SizeEvent(w, h)               => {
+            println!("Time: {}, Window size: ({}, {})", time, w, h);
+            app.ui.request_redraw();
+        }
         glfw::CloseEvent                    => println!("Time: {}, Window close requested.", time),
-        glfw::RefreshEvent                  => println!("Time: {}, Window refresh callback triggered.", time),
+        glfw::RefreshEvent                  => {
+            println!("Time: {}, Window refresh callback triggered.", time);
+            app.ui.request_redraw();
+        }
         glfw::FocusEvent(true)              => println!("Time: {}, Window focus gained.", time),
         glfw::FocusEvent(false)             => println!("Time: {}, Window focus lost.", time),
         glfw::IconifyEvent(true)            => println!("Time: {}, Window was minimised", time),
         glfw::IconifyEvent(false)           => println!("Time: {}, Window was maximised.", time),
-        glfw::FramebufferSizeEvent(w, h)    => println!("Time: {}, Framebuffer size: ({}, {})", time, w, h),
-        glfw::CharEvent(character)          => println!("Time: {}, Character: {}", time, character),
+        glfw::FramebufferSizeEvent(w, h)    => {
+            println!("Time: {}, Framebuffer size: ({}, {})", time, w, h);
+            app.ui.request_redraw();
+        }
+        glfw::CharEvent(character)          => app.ui.set_char(character),
         glfw::MouseButtonEvent(btn, action, mods) => println!("Time: {}, Button: {}, Action: {}, Modifiers: [{}]", time, glfw::ShowAliases(btn), action, mods),
         //glfw::CursorPosEvent(xpos, ypos)    => window.set_title(format!("Time: {}, Cursor position: ({}, {})", time, xpos, ypos).as_slice()),
         glfw::CursorEnterEvent(true)        => println!("Time: {}, Cursor entered window.", time),
         glfw::CursorEnterEvent(false)       => println!("Time: {}, Cursor left window.", time),
-        glfw::ScrollEvent(x, y)             => window.set_title(format!("Time: {}, Scroll offset: ({}, {})", time, x, y).as_slice()),
-        glfw::KeyEvent(key, _scancode, action, _mods) => {
+        glfw::KeyEvent(key, _scancode, action, mods) => {
+            app.ui.set_key(translate_key(key), translate_mods(mods), action != glfw::Release);
             //println!("Time: {}, Key: {}, ScanCode: {}, Action: {}, Modifiers: [{}]", time, key, scancode, action, mods);
             match (key, action) {
-                (glfw::KeyEscape, glfw::Press) => window.set_should_close(true),
-                (glfw::KeyR, glfw::Press) => {
+                (glfw::KeyR, glfw::Press) if app.ui.get_focus().invalid() => {
                     // Resize should cause the window to "refresh"
                     let (window_width, window_height) = window.get_size();
                     window.set_size(window_width + 1, window_height);
                     window.set_size(window_width, window_height);
                 }
+                (glfw::KeyZ, glfw::Press) if mods.contains(glfw::Control) => {
+                    if mods.contains(glfw::Shift) { app.ui.redo(); }
+                    else { app.ui.undo(); }
+                }
                 _ => {}
             }
         }
@@ -251,6 +331,78 @@ fn handle_window_event(
 }
 
 
+// glfw keys, as oui knows them
+fn translate_key(key: glfw::Key) -> Key {
+    match key {
+        glfw::KeyLeft => KEY_ARROW_LEFT,
+        glfw::KeyRight => KEY_ARROW_RIGHT,
+        glfw::KeyUp => KEY_ARROW_UP,
+        glfw::KeyDown => KEY_ARROW_DOWN,
+        glfw::KeyHome => KEY_HOME,
+        glfw::KeyEnd => KEY_END,
+        glfw::KeyPageUp => KEY_PAGE_UP,
+        glfw::KeyPageDown => KEY_PAGE_DOWN,
+        glfw::KeyBackspace => KEY_BACKSPACE,
+        glfw::KeyDelete => KEY_DELETE,
+        glfw::KeyEnter => KEY_ENTER,
+        glfw::KeyEscape => KEY_ESCAPE,
+        glfw::KeyTab => KEY_TAB,
+        other => KEY_CODE(other as u32)
+    }
+}
+
+fn translate_mods(mods: glfw::Modifiers) -> KeyMods {
+    let mut km = KeyMods::empty();
+    if mods.contains(glfw::Shift) { km = km | MOD_SHIFT; }
+    if mods.contains(glfw::Control) { km = km | MOD_CTRL; }
+    if mods.contains(glfw::Alt) { km = km | MOD_ALT; }
+    if mods.contains(glfw::Super) { km = km | MOD_SUPER; }
+    km
+}
+
+
+///////////////////////////////////////////////////////////////////////
+// a custom control: counts clicks on it
+
+struct Clicks {
+    count: u32,
+}
+
+impl Control for Clicks {
+    fn draw(&self, vg: &mut ThemedContext, rect: Rect, state: ItemState) {
+        let value = format!("{}", self.count);
+        vg.draw_number_field(rect.x, rect.y, rect.w, rect.h,
+            CORNER_NONE, state, "Clicks", value.as_slice());
+    }
+    fn handle_event(&mut self, _ui: &mut OUIContext<Widget>, _item: Item,
+        _info: &EventInfo) -> bool
+    {
+        self.count += 1;
+        true
+    }
+    fn label(&self) -> String { "Clicks".to_string() }
+}
+
+
+///////////////////////////////////////////////////////////////////////
+// menu commands
+
+fn commandhandler(ui: &mut OUIContext<Widget>, item: Item, _event: EventFlags) {
+    match ui.get_tag(item) {
+        31 => { ui.undo(); }
+        32 => { ui.redo(); }
+        tag => println!("command: #{}", tag),
+    }
+}
+
+fn edit_commands() -> Vec<MenuEntry> {
+    vec![
+        MENU_COMMAND(MenuCommand::new(31, "Undo", Some(commandhandler)).shortcut("Ctrl+Z")),
+        MENU_COMMAND(MenuCommand::new(32, "Redo", Some(commandhandler)).shortcut("Shift+Ctrl+Z")),
+    ]
+}
+
+
 ///////////////////////////////////////////////////////////////////////
 // ui
 
@@ -259,8 +411,15 @@ pub fn create() -> OUIContext<Widget> {
 }
 
 pub fn init(app: &mut App) {
+    build(&mut app.ui, &app.data);
+    // fit the widgets to their text, now that there's a font to measure with
+    let font = *app.themed.font();
+    let root = app.ui.root();
+    measure(&mut app.ui, root, &NanovgMetrics::new(app.themed.nvg(), &font));
+    app.ui.layout();
+}
 
-    let ui = &mut app.ui;
+pub fn build(ui: &mut OUIContext<Widget>, data: &AppData) {
 
     // setup the UI
 
@@ -274,21 +433,47 @@ pub fn init(app: &mut App) {
     // position root element
     ui.set_layout(root, LEFT|TOP);
     ui.set_margins(root, 60, 10, 0, 0);
-    ui.set_size(root, 450, 400);
+    ui.set_size(root, 450, 480);
 
     let col = column(ui, root);
     ui.set_margins(col, 10, 10, 10, 10);
     ui.set_layout(col, TOP|HFILL);
 
-    button(ui, col, 1, icon_id(6, 3), "Item 1", Some(demohandler));
-    button(ui, col, 2, icon_id(6, 3), "Item 2", Some(demohandler));
+    {
+        let bar = menubar(ui, col);
+        menubar_menu(ui, bar, 19, "File", vec![
+            MENU_COMMAND(MenuCommand::new(21, "New", Some(commandhandler)).shortcut("Ctrl+N")),
+            MENU_COMMAND(MenuCommand::new(22, "Open...", Some(commandhandler))
+                .icon(ICON_FILE_FOLDER).shortcut("Ctrl+O")),
+            MENU_SUBMENU(no_icon(), "Open Recent".to_string(), vec![
+                MENU_COMMAND(MenuCommand::new(23, "scene1.blend", Some(commandhandler))),
+                MENU_COMMAND(MenuCommand::new(24, "scene2.blend", Some(commandhandler))),
+            ]),
+            MENU_SEPARATOR,
+            MENU_COMMAND(MenuCommand::new(25, "Save", Some(commandhandler)).shortcut("Ctrl+S")),
+            MENU_COMMAND(MenuCommand::new(26, "Quit", Some(commandhandler)).shortcut("Ctrl+Q")),
+        ]);
+        let mut edit = edit_commands();
+        edit.push(MENU_SEPARATOR);
+        edit.push(MENU_COMMAND(MenuCommand::new(33, "Preferences...", Some(commandhandler))
+            .disabled()));
+        menubar_menu(ui, bar, 20, "Edit", edit);
+        menubar_menu(ui, bar, 27, "View", vec![
+            MENU_COMMAND(MenuCommand::new(28, "Show Grid", None).check(data.grid.clone())),
+        ]);
+    }
+
+    let item1 = button(ui, col, 1, ICON_GHOST, "Item 1", Some(demohandler));
+    ui.set_tooltip(item1, Tooltip::new("Print a message").shortcut("Ctrl+1")
+        .description("Prints the button's tag and label to the console"));
+    button(ui, col, 2, ICON_GHOST, "Item 2", Some(demohandler));
 
     {
         let h = hgroup(ui, col);
-        radio(ui, h, 3, icon_id(6,  3), "Item 3.0", app.data.enum1.clone());
-        radio(ui, h, 4, icon_id(0, 10), "", app.data.enum1.clone());
-        radio(ui, h, 5, icon_id(1, 10), "", app.data.enum1.clone());
-        radio(ui, h, 6, icon_id(6,  3), "Item 3.3", app.data.enum1.clone());
+        radio(ui, h, 3, ICON_GHOST, "Item 3.0", data.enum1.clone());
+        radio(ui, h, 4, ICON_REC, "", data.enum1.clone());
+        radio(ui, h, 5, ICON_PLAY, "", data.enum1.clone());
+        radio(ui, h, 6, ICON_GHOST, "Item 3.3", data.enum1.clone());
     }
 
     {
@@ -296,21 +481,52 @@ pub fn init(app: &mut App) {
         let left = vgroup(ui, row);
         label(ui, left, no_icon(), "Items 4.0:");
         let left_body = vgroup(ui, left);
-        button(ui, left_body, 7, icon_id(6, 3), "Item 4.0.0", Some(demohandler));
-        button(ui, left_body, 8, icon_id(6, 3), "Item 4.0.1", Some(demohandler));
+        button(ui, left_body, 7, ICON_GHOST, "Item 4.0.0", Some(demohandler));
+        button(ui, left_body, 8, ICON_GHOST, "Item 4.0.1", Some(demohandler));
         let right = vgroup(ui, row);
-        ui.set_frozen(right, app.data.option1.get()); // make initial gui state match data
+        ui.bind_frozen(right, &data.option1); // "Freeze section 4.1" below
         label(ui, right, no_icon(), "Items 4.1:");
         let right_body = vgroup(ui, right);
-        slider(ui, right_body,  9, "Item 4.1.0", app.data.progress1.clone());
-        slider(ui, right_body, 10, "Item 4.1.1", app.data.progress2.clone());
+        slider(ui, right_body,  9, "Item 4.1.0", data.progress1.clone());
+        slider(ui, right_body, 10, "Item 4.1.1", data.progress2.clone());
     }
 
-    button(ui, col, 11, icon_id(6, 3), "Item 5", None);
+    button(ui, col, 11, ICON_GHOST, "Item 5", None);
+    control(ui, col, 15, box Clicks { count: 0 });
+
+    check(ui, col, 12, "Freeze section 4.1", data.option1.clone(), Some(checkhandler));
+    check(ui, col, 13, "Item 7", data.option2.clone(), Some(checkhandler));
+    check(ui, col, 14, "Item 8", data.option3.clone(), Some(checkhandler));
+    textfield(ui, col, 16, no_icon(), data.name.clone());
+    numberfield(ui, col, 17, "Item 10", data.amount.clone(),
+        NumberSpec::new(0.0, 10.0, 0.5, 2));
+    choice(ui, col, 18, vec![
+        MENU_HEADING(no_icon(), "Primitives".to_string()),
+        MENU_OPTION(ICON_MESH_CUBE, "Cube".to_string()),
+        MENU_OPTION(ICON_MESH_UVSPHERE, "Sphere".to_string()),
+        MENU_OPTION(ICON_MESH_CYLINDER, "Cylinder".to_string()),
+        MENU_SEPARATOR,
+        MENU_OPTION(no_icon(), "Empty".to_string()),
+    ], data.shape.clone());
 
-    check(ui, col, 12, "Freeze section 4.1", app.data.option1.clone(), Some(freezehandler));
-    check(ui, col, 13, "Item 7", app.data.option2.clone(), Some(checkhandler));
-    check(ui, col, 14, "Item 8", app.data.option3.clone(), Some(checkhandler));
+    {
+        let list = scrollpanel(ui, col, 37, 60, data.scroll.clone());
+        for i in range(0u64, 6) {
+            let name = format!("Item 11.{}", i);
+            button(ui, list, 38 + i, ICON_GHOST, name.as_slice(), Some(demohandler));
+        }
+    }
+
+    // right-click anywhere in the panel
+    let mut entries = edit_commands();
+    entries.push(MENU_SEPARATOR);
+    entries.push(MENU_SUBMENU(no_icon(), "Add".to_string(), vec![
+        MENU_COMMAND(MenuCommand::new(34, "Plane", Some(commandhandler))),
+        MENU_COMMAND(MenuCommand::new(35, "Torus", Some(commandhandler))),
+    ]));
+    entries.push(MENU_COMMAND(MenuCommand::new(36, "Freeze Items 4.1", None)
+        .check(data.option1.clone())));
+    context_menu(ui, root, entries);
 
     // structure is built, append-handlers have run (so edge-grabbers are set);
     // now complete the layout
@@ -318,14 +534,25 @@ pub fn init(app: &mut App) {
     ui.layout();
 }
 
-pub fn update(ui: &mut OUIContext<Widget>, (mx,my): (i32,i32), btn: bool, _t: f32) {
+pub fn update(ui: &mut OUIContext<Widget>, metrics: &FontMetrics, (mx,my): (f32,f32),
+    btn: bool, t: f64)
+{
     // apply inputs: mouse and buttons, keys if needed
 
+    ui.set_time(t);
     ui.set_button(0/*left button*/, btn);
     ui.set_cursor(mx, my);
 
     // process input triggers to update item states
     ui.process();
+
+    // a property that affects layout has changed; a control may have
+    // changed size, too
+    if ui.needs_layout() {
+        let root = ui.root();
+        measure_controls(ui, root, metrics);
+        ui.layout();
+    }
 }
 
 pub fn draw(ui: &mut OUIContext<Widget>, ctx: &mut ThemedContext, w:f32, h:f32)
@@ -334,5 +561,436 @@ pub fn draw(ui: &mut OUIContext<Widget>, ctx: &mut ThemedContext, w:f32, h:f32)
     ctx.draw_background(0.0, 0.0, w, h);
 
     let root = ui.root();
-    draw_ui(ui, ctx, root, 0, 0);
+    draw_ui(ui, ctx, root, 0.0, 0.0);
+    draw_tooltip(ui, ctx, w, h);
+}
+
+
+///////////////////////////////////////////////////////////////////////
+// headless tests of the demo ui
+
+#[cfg(test)]
+fn test_ui() -> (OUIContext<Widget>, AppData) {
+    let mut ui = create();
+    let data = init_app_data();
+    build(&mut ui, &data);
+    (ui, data)
+}
+
+#[test]
+fn test_click_radio_selects_it() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    driver.assert_state("Item 3.0", ACTIVE);
+    driver.click("Item 3.3");
+    driver.assert_state("Item 3.3", ACTIVE);
+    driver.assert_state("Item 3.0", COLD);
+    assert_eq!(data.enum1.get(), 3);
+}
+
+#[test]
+fn test_click_check_toggles_it() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    driver.click("Item 7");
+    driver.assert_state("Item 7", ACTIVE);
+    assert!(data.option2.get());
+    driver.click(13u64);
+    // unchecked, and still under the cursor
+    driver.assert_state("Item 7", HOT);
+    assert!(!data.option2.get());
+}
+
+#[test]
+fn test_release_asks_for_a_redraw() {
+    let (mut ui, _data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    let c = driver.center_of("Item 3.3");
+    driver.move_to(c.x, c.y);
+    driver.press();
+    driver.ui.mark_drawn();
+    // still hot, but no longer pressed
+    driver.release();
+    assert!(driver.ui.needs_redraw());
+}
+
+#[test]
+fn test_frozen_section_exports_frozen() {
+    let (mut ui, _data) = test_ui();
+    let root = ui.root();
+    let tree = access_tree(&mut ui, root);
+    // the group is frozen, and so is what's in it
+    assert_eq!(tree.find("Item 4.1.0").unwrap().state, FROZEN);
+    let slider = tree.find("Item 4.1.0").unwrap().item;
+    assert_eq!(access_tree(&mut ui, slider).state, FROZEN);
+}
+
+// an exported node as a harness reads it back; what it doesn't need is
+// left out
+#[cfg(test)]
+#[deriving(Decodable)]
+struct Exported {
+    role: String,
+    name: String,
+    value: Option<ExportedValue>,
+    state: String,
+    bounds: ExportedBounds,
+    children: Vec<Exported>,
+}
+
+#[cfg(test)]
+#[deriving(Decodable)]
+struct ExportedValue {
+    checked: Option<bool>,
+}
+
+#[cfg(test)]
+#[deriving(Decodable, PartialEq, Show)]
+struct ExportedBounds {
+    x: f32, y: f32, w: f32, h: f32,
+}
+
+#[cfg(test)]
+impl Exported {
+    fn find(&self, name: &str) -> Option<&Exported> {
+        if self.name.as_slice() == name { return Some(self); }
+        self.children.iter().filter_map(|kid| kid.find(name)).next()
+    }
+}
+
+#[test]
+fn test_export_reads_back() {
+    let (mut ui, _data) = test_ui();
+    let rect = {
+        let mut driver = Driver::new(&mut ui);
+        driver.click("Item 7");
+        let item = driver.find("Item 7");
+        driver.ui.get_abs_rect(item)
+    };
+    let text = export_json(&mut ui);
+    let tree: Exported = json::decode(text.as_slice()).unwrap();
+    let node = tree.find("Item 7").unwrap();
+    assert_eq!(node.role.as_slice(), "checkbox");
+    assert_eq!(node.value.as_ref().and_then(|v| v.checked), Some(true));
+    // checked, and the cursor still on it
+    assert_eq!(node.state.as_slice(), "active");
+    assert_eq!(node.bounds, ExportedBounds { x: rect.x, y: rect.y, w: rect.w, h: rect.h });
+}
+
+#[test]
+fn test_frozen_slider_ignores_drags() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    driver.drag_slider("Item 4.1.0", 0.8);
+    assert_eq!(data.progress1.get(), 0.25);
+}
+
+#[test]
+fn test_unfreeze_and_drag_slider() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    driver.assert_state("Item 4.1.0", FROZEN);
+    driver.click("Freeze section 4.1");
+    driver.assert_state("Item 4.1.0", COLD);
+    driver.drag_slider("Item 4.1.0", 0.8);
+    assert!((data.progress1.get() - 0.8).abs() < 1e-3);
+    // the other slider is untouched
+    assert_eq!(data.progress2.get(), 0.75);
+}
+
+#[test]
+fn test_undo_redo_widget_changes() {
+    let (mut ui, data) = test_ui();
+    {
+        let mut driver = Driver::new(&mut ui);
+        driver.click("Freeze section 4.1");
+        driver.drag_slider("Item 4.1.0", 0.8);
+        driver.click("Item 3.3");
+    }
+    assert_eq!(data.enum1.get(), 3);
+    assert!(ui.undo());
+    assert_eq!(data.enum1.get(), 0);
+    // the whole drag comes back in one step
+    assert!(ui.undo());
+    assert_eq!(data.progress1.get(), 0.25);
+    assert!(ui.undo());
+    assert!(data.option1.get());
+    assert!(!ui.undo());
+    assert!(ui.redo());
+    assert!(ui.redo());
+    assert!((data.progress1.get() - 0.8).abs() < 1e-3);
+}
+
+#[test]
+fn test_edit_text_field() {
+    let (mut ui, data) = test_ui();
+    {
+        let mut driver = Driver::new(&mut ui);
+        driver.click(16u64);
+        driver.assert_state(16u64, ACTIVE);
+        driver.key(KEY_END, KeyMods::empty());
+        driver.type_text(" two");
+        // not committed yet
+        assert_eq!(data.name.get().as_slice(), "Item 9");
+        driver.key(KEY_ENTER, KeyMods::empty());
+        driver.assert_state(16u64, HOT);
+    }
+    assert_eq!(data.name.get().as_slice(), "Item 9 two");
+    assert!(ui.undo());
+    assert_eq!(data.name.get().as_slice(), "Item 9");
+}
+
+#[test]
+fn test_escape_cancels_text_edit() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    driver.click(16u64);
+    driver.key(KEY_BACKSPACE, MOD_CTRL);
+    driver.type_text("x");
+    driver.key(KEY_ESCAPE, KeyMods::empty());
+    assert_eq!(data.name.get().as_slice(), "Item 9");
+}
+
+#[test]
+fn test_drag_number_field() {
+    let (mut ui, data) = test_ui();
+    {
+        let mut driver = Driver::new(&mut ui);
+        // 0.125 per pixel
+        driver.drag("Item 10", 40.0, 0.0);
+    }
+    assert!((data.amount.get() - 7.5).abs() < 1e-3);
+    assert!(ui.undo());
+    assert_eq!(data.amount.get(), 2.5);
+}
+
+#[test]
+fn test_number_field_arrows_and_typing() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    let item = driver.find("Item 10");
+    let rect = driver.ui.get_abs_rect(item);
+    driver.move_to(rect.x + rect.w - 4.0, rect.y + rect.h*0.5);
+    driver.press();
+    driver.release();
+    assert_eq!(data.amount.get(), 3.0);
+
+    // double-click to type; the whole value is selected
+    driver.click("Item 10");
+    driver.click("Item 10");
+    driver.assert_state("Item 10", ACTIVE);
+    driver.type_text("4.25");
+    driver.key(KEY_ENTER, KeyMods::empty());
+    assert_eq!(data.amount.get(), 4.25);
+
+    // out of range is clamped, nonsense is dropped
+    driver.click("Item 10");
+    driver.click("Item 10");
+    driver.type_text("12");
+    driver.key(KEY_ENTER, KeyMods::empty());
+    assert_eq!(data.amount.get(), 10.0);
+    driver.click("Item 10");
+    driver.click("Item 10");
+    driver.type_text("abc");
+    driver.key(KEY_ENTER, KeyMods::empty());
+    assert_eq!(data.amount.get(), 10.0);
+}
+
+#[test]
+fn test_choose_option_with_mouse() {
+    let (mut ui, data) = test_ui();
+    {
+        let mut driver = Driver::new(&mut ui);
+        driver.click(18u64);
+        driver.assert_state(18u64, ACTIVE);
+        driver.click("Sphere");
+        assert!(driver.state(18u64) != ACTIVE);
+    }
+    assert_eq!(data.shape.get(), 1);
+    assert!(ui.undo());
+    assert_eq!(data.shape.get(), 0);
+}
+
+#[test]
+fn test_choose_option_with_keyboard() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    // the heading and the separator are skipped
+    driver.click(18u64);
+    driver.key(KEY_ARROW_DOWN, KeyMods::empty());
+    driver.key(KEY_ARROW_DOWN, KeyMods::empty());
+    driver.key(KEY_ARROW_DOWN, KeyMods::empty());
+    driver.key(KEY_ENTER, KeyMods::empty());
+    assert_eq!(data.shape.get(), 3);
+    driver.click(18u64);
+    driver.key(KEY_ARROW_DOWN, KeyMods::empty());
+    driver.key(KEY_ENTER, KeyMods::empty());
+    assert_eq!(data.shape.get(), 0);
+}
+
+#[test]
+fn test_menu_closes_without_choosing() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    driver.click(18u64);
+    driver.key(KEY_ARROW_DOWN, KeyMods::empty());
+    driver.key(KEY_ESCAPE, KeyMods::empty());
+    assert!(driver.state(18u64) != ACTIVE);
+    // a click outside closes it
+    driver.click(18u64);
+    driver.click("Item 1");
+    assert!(driver.state(18u64) != ACTIVE);
+    assert_eq!(data.shape.get(), 0);
+}
+
+#[test]
+fn test_menubar_check_and_undo() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    driver.click("View");
+    driver.assert_state("View", ACTIVE);
+    driver.click("Show Grid");
+    assert!(driver.state("View") != ACTIVE);
+    assert!(!data.grid.get());
+    // the toggle is one undo step
+    driver.click("Edit");
+    driver.click("Undo");
+    assert!(data.grid.get());
+}
+
+#[test]
+fn test_submenu_opens_after_delay() {
+    let (mut ui, _data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    driver.click("File");
+    let c = driver.center_of("Open Recent");
+    driver.move_to(c.x, c.y);
+    driver.wait(SUBMENU_DELAY * 0.5);
+    assert!("scene1.blend".locate(&mut *driver.ui).is_none());
+    driver.wait(SUBMENU_DELAY);
+    assert!("scene1.blend".locate(&mut *driver.ui).is_some());
+    // Escape closes one level at a time
+    driver.key(KEY_ESCAPE, KeyMods::empty());
+    assert!("scene1.blend".locate(&mut *driver.ui).is_none());
+    driver.assert_state("File", ACTIVE);
+    driver.key(KEY_ESCAPE, KeyMods::empty());
+    assert!(driver.state("File") != ACTIVE);
+}
+
+#[test]
+fn test_context_menu_toggles_check() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    driver.context_click("Item 5");
+    driver.click("Freeze Items 4.1");
+    assert!(!data.option1.get());
+    assert!("Freeze Items 4.1".locate(&mut *driver.ui).is_none());
+}
+
+#[test]
+fn test_disabled_entry_keeps_menu_open() {
+    let (mut ui, _data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    driver.click("Edit");
+    driver.assert_state("Preferences...", FROZEN);
+    driver.click("Preferences...");
+    driver.assert_state("Edit", ACTIVE);
+}
+
+#[test]
+fn test_drag_scrollbar_handle() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    let panel = driver.find(37u64);
+    let view = driver.ui.get_abs_rect(panel);
+    let bar = driver.ui.last_child(panel);
+    let track = driver.ui.get_abs_rect(bar);
+    // the handle starts at the top of the track
+    driver.move_to(track.x + track.w*0.5, track.y + 4.0);
+    driver.press();
+    driver.move_to(track.x + track.w*0.5, track.y + track.h);
+    driver.release();
+    assert_eq!(data.scroll.get(), 1.0);
+    // the last button has scrolled into view, the first out of it
+    let last = driver.center_of("Item 11.5");
+    assert!(last.y > view.y && last.y < view.y + view.h);
+    let first = driver.center_of("Item 11.0");
+    assert!(first.y < view.y);
+}
+
+#[test]
+fn test_scrolled_out_items_ignore_the_cursor() {
+    let (mut ui, data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    let panel = driver.find(37u64);
+    let bar = driver.ui.last_child(panel);
+    let track = driver.ui.get_abs_rect(bar);
+    // clicking the track below the handle pages down
+    driver.move_to(track.x + track.w*0.5, track.y + track.h - 2.0);
+    driver.press();
+    driver.release();
+    assert!(data.scroll.get() > 0.0);
+    let first = driver.center_of("Item 11.0");
+    driver.move_to(first.x, first.y);
+    driver.assert_state("Item 11.0", COLD);
+}
+
+#[test]
+fn test_tooltip_after_hover_delay() {
+    let (mut ui, _data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    let item1 = driver.find("Item 1");
+    let c = driver.center_of(item1);
+    driver.move_to(c.x, c.y);
+    driver.wait(0.25);
+    assert!(driver.ui.get_tooltip_item().invalid());
+    driver.wait(0.5);
+    assert_eq!(driver.ui.get_tooltip_item(), item1);
+    assert_eq!(driver.ui.get_tooltip_pos(), c);
+    // moving hides it
+    driver.move_to(c.x + 2.0, c.y);
+    assert!(driver.ui.get_tooltip_item().invalid());
+    // so does a click, until the cursor moves again
+    driver.wait(1.0);
+    assert_eq!(driver.ui.get_tooltip_item(), item1);
+    driver.press();
+    driver.release();
+    driver.wait(1.0);
+    assert!(driver.ui.get_tooltip_item().invalid());
+    driver.move_to(c.x, c.y);
+    driver.wait(1.0);
+    assert_eq!(driver.ui.get_tooltip_item(), item1);
+}
+
+#[test]
+fn test_no_tooltip_elsewhere() {
+    let (mut ui, _data) = test_ui();
+    let mut driver = Driver::new(&mut ui);
+    let c = driver.center_of("Item 2");
+    driver.move_to(c.x, c.y);
+    driver.wait(1.0);
+    assert!(driver.ui.get_tooltip_item().invalid());
+}
+
+#[test]
+fn test_measured_menubar_fits_titles() {
+    let (mut ui, _data) = test_ui();
+    let root = ui.root();
+    measure(&mut ui, root, &FixedMetrics { advance: 7.0 });
+    ui.layout();
+    let mut driver = Driver::new(&mut ui);
+    let pad = (PAD_LEFT + PAD_RIGHT) as f32;
+    let file = driver.find("File");
+    let edit = driver.find("Edit");
+    let file_rect = driver.ui.get_rect(file);
+    let edit_rect = driver.ui.get_rect(edit);
+    assert_eq!(file_rect.w, pad + 28.0);
+    assert_eq!(edit_rect.x, file_rect.x + file_rect.w);
+    // the open menu is wide enough for its widest entry
+    driver.click("File");
+    let recent = driver.find("Open Recent");
+    let open = driver.find("Open...");
+    let menu = driver.ui.parent(recent);
+    let widest = driver.ui.get_preferred_size(open);
+    assert!(driver.ui.get_rect(menu).w >= widest.x);
 }
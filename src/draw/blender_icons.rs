@@ -0,0 +1,578 @@
+use std::ascii::StrAsciiExt;
+
+// names for the icons of res/blender_icons16.png, as (column, row) on the
+// sheet, (0,0) the upper-leftmost. the sheet is Blender's, filled in the
+// order of its UI_icons.h from the bottom row up, and the names are its own.
+// the cells it leaves blank have no name, and neither do the five icons it
+// calls BLANKnnn or has no entry for: (20,29), (22,23) and row 15.
+macro_rules! icons(
+    ($($name:ident = ($x:expr, $y:expr)),+) => (
+        $(pub static $name: i32 = $x | ($y << 8);)+
+
+        static ICON_NAMES: &'static [(&'static str, i32)] = &[
+            $((stringify!($name), $name)),+
+        ];
+    )
+)
+
+icons!(
+    // sequencer and image editor
+    ICON_SEQ_SEQUENCER          = ( 0,  0),
+    ICON_SEQ_PREVIEW            = ( 1,  0),
+    ICON_SEQ_LUMA_WAVEFORM      = ( 2,  0),
+    ICON_SEQ_CHROMA_SCOPE       = ( 3,  0),
+    ICON_SEQ_HISTOGRAM          = ( 4,  0),
+    ICON_SEQ_SPLITVIEW          = ( 5,  0),
+    ICON_IMAGE_RGB              = ( 9,  0),
+    ICON_IMAGE_RGB_ALPHA        = (10,  0),
+    ICON_IMAGE_ALPHA            = (11,  0),
+    ICON_IMAGE_ZDEPTH           = (12,  0),
+    ICON_IMAGEFILE              = (13,  0),
+
+    // shading and text editor
+    ICON_MATPLANE               = ( 0,  1),
+    ICON_MATSPHERE              = ( 1,  1),
+    ICON_MATCUBE                = ( 2,  1),
+    ICON_MONKEY                 = ( 3,  1),
+    ICON_HAIR                   = ( 4,  1),
+    ICON_ALIASED                = ( 5,  1),
+    ICON_ANTIALIASED            = ( 6,  1),
+    ICON_MAT_SPHERE_SKY         = ( 7,  1),
+    ICON_WORDWRAP_OFF           = (12,  1),
+    ICON_WORDWRAP_ON            = (13,  1),
+    ICON_SYNTAX_OFF             = (14,  1),
+    ICON_SYNTAX_ON              = (15,  1),
+    ICON_LINENUMBERS_OFF        = (16,  1),
+    ICON_LINENUMBERS_ON         = (17,  1),
+    ICON_SCRIPTPLUGINS          = (18,  1),
+
+    // files
+    ICON_RECOVER_AUTO           = ( 0,  2),
+    ICON_SAVE_PREFS             = ( 1,  2),
+    ICON_LINK_BLEND             = ( 2,  2),
+    ICON_APPEND_BLEND           = ( 3,  2),
+    ICON_IMPORT                 = ( 4,  2),
+    ICON_EXPORT                 = ( 5,  2),
+    ICON_EXTERNAL_DATA          = ( 6,  2),
+    ICON_LOAD_FACTORY           = ( 7,  2),
+    ICON_LOOP_BACK              = (13,  2),
+    ICON_LOOP_FORWARDS          = (14,  2),
+    ICON_BACK                   = (15,  2),
+    ICON_FORWARD                = (16,  2),
+    ICON_FILE_BACKUP            = (24,  2),
+    ICON_DISK_DRIVE             = (25,  2),
+
+    // file browser
+    ICON_SORTALPHA              = ( 0,  3),
+    ICON_SORTBYEXT              = ( 1,  3),
+    ICON_SORTTIME               = ( 2,  3),
+    ICON_SORTSIZE               = ( 3,  3),
+    ICON_LONGDISPLAY            = ( 4,  3),
+    ICON_SHORTDISPLAY           = ( 5,  3),
+    ICON_GHOST                  = ( 6,  3),
+    ICON_IMGDISPLAY             = ( 7,  3),
+    ICON_SAVE_AS                = ( 8,  3),
+    ICON_SAVE_COPY              = ( 9,  3),
+    ICON_BOOKMARKS              = (10,  3),
+    ICON_FONTPREVIEW            = (11,  3),
+    ICON_FILTER                 = (12,  3),
+    ICON_NEWFOLDER              = (13,  3),
+    ICON_OPEN_RECENT            = (14,  3),
+    ICON_FILE_PARENT            = (15,  3),
+    ICON_FILE_REFRESH           = (16,  3),
+    ICON_FILE_FOLDER            = (17,  3),
+    ICON_FILE_BLANK             = (18,  3),
+    ICON_FILE_BLEND             = (19,  3),
+    ICON_FILE_IMAGE             = (20,  3),
+    ICON_FILE_MOVIE             = (21,  3),
+    ICON_FILE_SCRIPT            = (22,  3),
+    ICON_FILE_SOUND             = (23,  3),
+    ICON_FILE_FONT              = (24,  3),
+    ICON_FILE_TEXT              = (25,  3),
+
+    // 3D view
+    ICON_BBOX                   = ( 0,  5),
+    ICON_WIRE                   = ( 1,  5),
+    ICON_SOLID                  = ( 2,  5),
+    ICON_SMOOTH                 = ( 3,  5),
+    ICON_POTATO                 = ( 4,  5),
+    ICON_ORTHO                  = ( 6,  5),
+    ICON_LOCKVIEW_OFF           = ( 9,  5),
+    ICON_LOCKVIEW_ON            = (10,  5),
+    ICON_AXIS_SIDE              = (12,  5),
+    ICON_AXIS_FRONT             = (13,  5),
+    ICON_AXIS_TOP               = (14,  5),
+    ICON_NDOF_DOM               = (15,  5),
+    ICON_NDOF_TURN              = (16,  5),
+    ICON_NDOF_FLY               = (17,  5),
+    ICON_NDOF_TRANS             = (18,  5),
+    ICON_LAYER_USED             = (19,  5),
+    ICON_LAYER_ACTIVE           = (20,  5),
+
+    // editing
+    ICON_PASTEDOWN              = ( 0,  6),
+    ICON_COPYDOWN               = ( 1,  6),
+    ICON_PASTEFLIPUP            = ( 2,  6),
+    ICON_PASTEFLIPDOWN          = ( 3,  6),
+    ICON_SNAP_SURFACE           = ( 8,  6),
+    ICON_AUTOMERGE_ON           = ( 9,  6),
+    ICON_AUTOMERGE_OFF          = (10,  6),
+    ICON_RETOPO                 = (11,  6),
+    ICON_UV_VERTEXSEL           = (12,  6),
+    ICON_UV_EDGESEL             = (13,  6),
+    ICON_UV_FACESEL             = (14,  6),
+    ICON_UV_ISLANDSEL           = (15,  6),
+    ICON_UV_SYNC_SELECT         = (16,  6),
+    ICON_MAN_TRANS              = ( 0,  7),
+    ICON_MAN_ROT                = ( 1,  7),
+    ICON_MAN_SCALE              = ( 2,  7),
+    ICON_MANIPUL                = ( 3,  7),
+    ICON_SNAP_OFF               = ( 4,  7),
+    ICON_SNAP_ON                = ( 5,  7),
+    ICON_SNAP_NORMAL            = ( 6,  7),
+    ICON_SNAP_INCREMENT         = ( 7,  7),
+    ICON_SNAP_VERTEX            = ( 8,  7),
+    ICON_SNAP_EDGE              = ( 9,  7),
+    ICON_SNAP_FACE              = (10,  7),
+    ICON_SNAP_VOLUME            = (11,  7),
+    ICON_STICKY_UVS_LOC         = (13,  7),
+    ICON_STICKY_UVS_DISABLE     = (14,  7),
+    ICON_STICKY_UVS_VERT        = (15,  7),
+    ICON_CLIPUV_DEHLT           = (16,  7),
+    ICON_CLIPUV_HLT             = (17,  7),
+    ICON_SNAP_PEEL_OBJECT       = (18,  7),
+    ICON_GRID                   = (19,  7),
+    ICON_VERTEXSEL              = ( 0,  8),
+    ICON_EDGESEL                = ( 1,  8),
+    ICON_FACESEL                = ( 2,  8),
+    ICON_ROTATE                 = ( 5,  8),
+    ICON_CURSOR                 = ( 6,  8),
+    ICON_ROTATECOLLECTION       = ( 7,  8),
+    ICON_ROTATECENTER           = ( 8,  8),
+    ICON_ROTACTIVE              = ( 9,  8),
+    ICON_ALIGN                  = (10,  8),
+    ICON_SMOOTHCURVE            = (12,  8),
+    ICON_SPHERECURVE            = (13,  8),
+    ICON_ROOTCURVE              = (14,  8),
+    ICON_SHARPCURVE             = (15,  8),
+    ICON_LINCURVE               = (16,  8),
+    ICON_NOCURVE                = (17,  8),
+    ICON_RNDCURVE               = (18,  8),
+    ICON_PROP_OFF               = (19,  8),
+    ICON_PROP_ON                = (20,  8),
+    ICON_PROP_CON               = (21,  8),
+    ICON_PARTICLE_POINT         = (23,  8),
+    ICON_PARTICLE_TIP           = (24,  8),
+    ICON_PARTICLE_PATH          = (25,  8),
+
+    // animation
+    ICON_SOLO_OFF               = ( 0,  9),
+    ICON_SOLO_ON                = ( 1,  9),
+    ICON_FRAME_PREV             = ( 2,  9),
+    ICON_FRAME_NEXT             = ( 3,  9),
+    ICON_REC                    = ( 0, 10),
+    ICON_PLAY                   = ( 1, 10),
+    ICON_FF                     = ( 2, 10),
+    ICON_REW                    = ( 3, 10),
+    ICON_PAUSE                  = ( 4, 10),
+    ICON_PREV_KEYFRAME          = ( 5, 10),
+    ICON_NEXT_KEYFRAME          = ( 6, 10),
+    ICON_PLAY_AUDIO             = ( 7, 10),
+    ICON_PLAY_REVERSE           = ( 8, 10),
+    ICON_PREVIEW_RANGE          = ( 9, 10),
+    ICON_PMARKER_ACT            = (11, 10),
+    ICON_PMARKER_SEL            = (12, 10),
+    ICON_PMARKER                = (13, 10),
+    ICON_MARKER_HLT             = (14, 10),
+    ICON_MARKER                 = (15, 10),
+    ICON_SPACE2                 = (16, 10),
+    ICON_SPACE3                 = (17, 10),
+    ICON_KEYINGSET              = (18, 10),
+    ICON_KEY_DEHLT              = (19, 10),
+    ICON_KEY_HLT                = (20, 10),
+    ICON_MUTE_IPO_OFF           = (21, 10),
+    ICON_MUTE_IPO_ON            = (22, 10),
+    ICON_VISIBLE_IPO_OFF        = (23, 10),
+    ICON_VISIBLE_IPO_ON         = (24, 10),
+    ICON_DRIVER                 = (25, 10),
+
+    // modifiers
+    ICON_MOD_CLOTH              = ( 0, 11),
+    ICON_MOD_EXPLODE            = ( 1, 11),
+    ICON_MOD_FLUIDSIM           = ( 2, 11),
+    ICON_MOD_MULTIRES           = ( 3, 11),
+    ICON_MOD_SMOKE              = ( 4, 11),
+    ICON_MOD_SOLIDIFY           = ( 5, 11),
+    ICON_MOD_SCREW              = ( 6, 11),
+    ICON_MOD_VERTEX_WEIGHT      = ( 7, 11),
+    ICON_MOD_DYNAMICPAINT       = ( 8, 11),
+    ICON_MOD_REMESH             = ( 9, 11),
+    ICON_MOD_OCEAN              = (10, 11),
+    ICON_MOD_WARP               = (11, 11),
+    ICON_MOD_SKIN               = (12, 11),
+    ICON_MOD_TRIANGULATE        = (13, 11),
+    ICON_MODIFIER               = ( 0, 12),
+    ICON_MOD_WAVE               = ( 1, 12),
+    ICON_MOD_BUILD              = ( 2, 12),
+    ICON_MOD_DECIM              = ( 3, 12),
+    ICON_MOD_MIRROR             = ( 4, 12),
+    ICON_MOD_SOFT               = ( 5, 12),
+    ICON_MOD_SUBSURF            = ( 6, 12),
+    ICON_HOOK                   = ( 7, 12),
+    ICON_MOD_PHYSICS            = ( 8, 12),
+    ICON_MOD_PARTICLES          = ( 9, 12),
+    ICON_MOD_BOOLEAN            = (10, 12),
+    ICON_MOD_EDGESPLIT          = (11, 12),
+    ICON_MOD_ARRAY              = (12, 12),
+    ICON_MOD_UVPROJECT          = (13, 12),
+    ICON_MOD_DISPLACE           = (14, 12),
+    ICON_MOD_CURVE              = (15, 12),
+    ICON_MOD_LATTICE            = (16, 12),
+    ICON_CONSTRAINT_DATA        = (17, 12),
+    ICON_MOD_ARMATURE           = (18, 12),
+    ICON_MOD_SHRINKWRAP         = (19, 12),
+    ICON_MOD_CAST               = (20, 12),
+    ICON_MOD_MESHDEFORM         = (21, 12),
+    ICON_MOD_BEVEL              = (22, 12),
+    ICON_MOD_SMOOTH             = (23, 12),
+    ICON_MOD_SIMPLEDEFORM       = (24, 12),
+    ICON_MOD_MASK               = (25, 12),
+
+    // force fields
+    ICON_FORCE_FORCE            = ( 0, 16),
+    ICON_FORCE_WIND             = ( 1, 16),
+    ICON_FORCE_VORTEX           = ( 2, 16),
+    ICON_FORCE_MAGNETIC         = ( 3, 16),
+    ICON_FORCE_HARMONIC         = ( 4, 16),
+    ICON_FORCE_CHARGE           = ( 5, 16),
+    ICON_FORCE_LENNARDJONES     = ( 6, 16),
+    ICON_FORCE_TEXTURE          = ( 7, 16),
+    ICON_FORCE_CURVE            = ( 8, 16),
+    ICON_FORCE_BOID             = ( 9, 16),
+    ICON_FORCE_TURBULENCE       = (10, 16),
+    ICON_FORCE_DRAG             = (11, 16),
+    ICON_FORCE_SMOKEFLOW        = (12, 16),
+
+    // primitives
+    ICON_SURFACE_NCURVE         = ( 0, 17),
+    ICON_SURFACE_NCIRCLE        = ( 1, 17),
+    ICON_SURFACE_NSURFACE       = ( 2, 17),
+    ICON_SURFACE_NCYLINDER      = ( 3, 17),
+    ICON_SURFACE_NSPHERE        = ( 4, 17),
+    ICON_SURFACE_NTORUS         = ( 5, 17),
+    ICON_CURVE_BEZCURVE         = ( 9, 17),
+    ICON_CURVE_BEZCIRCLE        = (10, 17),
+    ICON_CURVE_NCURVE           = (11, 17),
+    ICON_CURVE_NCIRCLE          = (12, 17),
+    ICON_CURVE_PATH             = (13, 17),
+    ICON_COLOR_RED              = (19, 17),
+    ICON_COLOR_GREEN            = (20, 17),
+    ICON_COLOR_BLUE             = (21, 17),
+    ICON_MESH_PLANE             = ( 0, 18),
+    ICON_MESH_CUBE              = ( 1, 18),
+    ICON_MESH_CIRCLE            = ( 2, 18),
+    ICON_MESH_UVSPHERE          = ( 3, 18),
+    ICON_MESH_ICOSPHERE         = ( 4, 18),
+    ICON_MESH_GRID              = ( 5, 18),
+    ICON_MESH_MONKEY            = ( 6, 18),
+    ICON_MESH_CYLINDER          = ( 7, 18),
+    ICON_MESH_TORUS             = ( 8, 18),
+    ICON_MESH_CONE              = ( 9, 18),
+    ICON_LAMP_POINT             = (12, 18),
+    ICON_LAMP_SUN               = (13, 18),
+    ICON_LAMP_SPOT              = (14, 18),
+    ICON_LAMP_HEMI              = (15, 18),
+    ICON_LAMP_AREA              = (16, 18),
+    ICON_META_EMPTY             = (19, 18),
+    ICON_META_PLANE             = (20, 18),
+    ICON_META_CUBE              = (21, 18),
+    ICON_META_BALL              = (22, 18),
+    ICON_META_ELLIPSOID         = (23, 18),
+    ICON_META_CAPSULE           = (24, 18),
+
+    // outliner
+    ICON_OUTLINER_DATA_EMPTY    = ( 0, 19),
+    ICON_OUTLINER_DATA_MESH     = ( 1, 19),
+    ICON_OUTLINER_DATA_CURVE    = ( 2, 19),
+    ICON_OUTLINER_DATA_LATTICE  = ( 3, 19),
+    ICON_OUTLINER_DATA_META     = ( 4, 19),
+    ICON_OUTLINER_DATA_LAMP     = ( 5, 19),
+    ICON_OUTLINER_DATA_CAMERA   = ( 6, 19),
+    ICON_OUTLINER_DATA_ARMATURE = ( 7, 19),
+    ICON_OUTLINER_DATA_FONT     = ( 8, 19),
+    ICON_OUTLINER_DATA_SURFACE  = ( 9, 19),
+    ICON_OUTLINER_DATA_SPEAKER  = (10, 19),
+    ICON_OUTLINER_DATA_POSE     = (11, 19),
+    ICON_OUTLINER_OB_EMPTY      = ( 0, 20),
+    ICON_OUTLINER_OB_MESH       = ( 1, 20),
+    ICON_OUTLINER_OB_CURVE      = ( 2, 20),
+    ICON_OUTLINER_OB_LATTICE    = ( 3, 20),
+    ICON_OUTLINER_OB_META       = ( 4, 20),
+    ICON_OUTLINER_OB_LAMP       = ( 5, 20),
+    ICON_OUTLINER_OB_CAMERA     = ( 6, 20),
+    ICON_OUTLINER_OB_ARMATURE   = ( 7, 20),
+    ICON_OUTLINER_OB_FONT       = ( 8, 20),
+    ICON_OUTLINER_OB_SURFACE    = ( 9, 20),
+    ICON_OUTLINER_OB_SPEAKER    = (10, 20),
+    ICON_RESTRICT_VIEW_OFF      = (19, 20),
+    ICON_RESTRICT_VIEW_ON       = (20, 20),
+    ICON_RESTRICT_SELECT_OFF    = (21, 20),
+    ICON_RESTRICT_SELECT_ON     = (22, 20),
+    ICON_RESTRICT_RENDER_OFF    = (23, 20),
+    ICON_RESTRICT_RENDER_ON     = (24, 20),
+
+    // data
+    ICON_BRUSH_DATA             = ( 0, 22),
+    ICON_IMAGE_DATA             = ( 1, 22),
+    ICON_FILE                   = ( 2, 22),
+    ICON_FCURVE                 = ( 3, 22),
+    ICON_FONT_DATA              = ( 4, 22),
+    ICON_RENDER_RESULT          = ( 5, 22),
+    ICON_SURFACE_DATA           = ( 6, 22),
+    ICON_EMPTY_DATA             = ( 7, 22),
+    ICON_SETTINGS               = ( 8, 22),
+    ICON_RENDER_ANIMATION       = ( 9, 22),
+    ICON_RENDER_STILL           = (10, 22),
+    ICON_BOIDS                  = (12, 22),
+    ICON_STRANDS                = (13, 22),
+    ICON_LIBRARY_DATA_INDIRECT  = (14, 22),
+    ICON_GREASEPENCIL           = (15, 22),
+    ICON_GROUP_BONE             = (18, 22),
+    ICON_GROUP_VERTEX           = (19, 22),
+    ICON_GROUP_VCOL             = (20, 22),
+    ICON_GROUP_UVS              = (21, 22),
+    ICON_RNA                    = (24, 22),
+    ICON_RNA_ADD                = (25, 22),
+    ICON_SCENE_DATA             = ( 0, 23),
+    ICON_RENDERLAYERS           = ( 1, 23),
+    ICON_WORLD_DATA             = ( 2, 23),
+    ICON_OBJECT_DATA            = ( 3, 23),
+    ICON_MESH_DATA              = ( 4, 23),
+    ICON_CURVE_DATA             = ( 5, 23),
+    ICON_META_DATA              = ( 6, 23),
+    ICON_LATTICE_DATA           = ( 7, 23),
+    ICON_LAMP_DATA              = ( 8, 23),
+    ICON_MATERIAL_DATA          = ( 9, 23),
+    ICON_TEXTURE_DATA           = (10, 23),
+    ICON_ANIM_DATA              = (11, 23),
+    ICON_CAMERA_DATA            = (12, 23),
+    ICON_PARTICLE_DATA          = (13, 23),
+    ICON_LIBRARY_DATA_DIRECT    = (14, 23),
+    ICON_GROUP                  = (15, 23),
+    ICON_ARMATURE_DATA          = (16, 23),
+    ICON_POSE_DATA              = (17, 23),
+    ICON_BONE_DATA              = (18, 23),
+    ICON_CONSTRAINT             = (19, 23),
+    ICON_SHAPEKEY_DATA          = (20, 23),
+    ICON_CONSTRAINT_BONE        = (21, 23),
+    ICON_PACKAGE                = (23, 23),
+    ICON_UGLYPACKAGE            = (24, 23),
+
+    // modes
+    ICON_OBJECT_DATAMODE        = ( 0, 24),
+    ICON_EDITMODE_HLT           = ( 1, 24),
+    ICON_FACESEL_HLT            = ( 2, 24),
+    ICON_VPAINT_HLT             = ( 3, 24),
+    ICON_TPAINT_HLT             = ( 4, 24),
+    ICON_WPAINT_HLT             = ( 5, 24),
+    ICON_SCULPTMODE_HLT         = ( 6, 24),
+    ICON_POSE_HLT               = ( 7, 24),
+    ICON_PARTICLEMODE           = ( 8, 24),
+    ICON_LIGHTPAINT             = ( 9, 24),
+
+    // editors
+    ICON_VIEW3D                 = ( 0, 25),
+    ICON_IPO                    = ( 1, 25),
+    ICON_OOPS                   = ( 2, 25),
+    ICON_BUTS                   = ( 3, 25),
+    ICON_FILESEL                = ( 4, 25),
+    ICON_IMAGE_COL              = ( 5, 25),
+    ICON_INFO                   = ( 6, 25),
+    ICON_SEQUENCE               = ( 7, 25),
+    ICON_TEXT                   = ( 8, 25),
+    ICON_IMASEL                 = ( 9, 25),
+    ICON_SOUND                  = (10, 25),
+    ICON_ACTION                 = (11, 25),
+    ICON_NLA                    = (12, 25),
+    ICON_SCRIPTWIN              = (13, 25),
+    ICON_TIME                   = (14, 25),
+    ICON_NODETREE               = (15, 25),
+    ICON_LOGIC                  = (16, 25),
+    ICON_CONSOLE                = (17, 25),
+    ICON_PREFERENCES            = (18, 25),
+    ICON_CLIP                   = (19, 25),
+    ICON_ASSET_MANAGER          = (20, 25),
+
+    // properties editor tabs
+    ICON_LAMP                   = ( 0, 26),
+    ICON_MATERIAL               = ( 1, 26),
+    ICON_TEXTURE                = ( 2, 26),
+    ICON_ANIM                   = ( 3, 26),
+    ICON_WORLD                  = ( 4, 26),
+    ICON_SCENE                  = ( 5, 26),
+    ICON_EDIT                   = ( 6, 26),
+    ICON_GAME                   = ( 7, 26),
+    ICON_RADIO                  = ( 8, 26),
+    ICON_SCRIPT                 = ( 9, 26),
+    ICON_PARTICLES              = (10, 26),
+    ICON_PHYSICS                = (11, 26),
+    ICON_SPEAKER                = (12, 26),
+    ICON_TEXTURE_SHADED         = (13, 26),
+
+    // general interface
+    ICON_HELP                   = ( 0, 27),
+    ICON_GHOST_ENABLED          = ( 1, 27),
+    ICON_COLOR                  = ( 2, 27),
+    ICON_LINKED                 = ( 3, 27),
+    ICON_UNLINKED               = ( 4, 27),
+    ICON_HAND                   = ( 5, 27),
+    ICON_ZOOM_ALL               = ( 6, 27),
+    ICON_ZOOM_SELECTED          = ( 7, 27),
+    ICON_ZOOM_PREVIOUS          = ( 8, 27),
+    ICON_ZOOM_IN                = ( 9, 27),
+    ICON_ZOOM_OUT               = (10, 27),
+    ICON_RENDER_REGION          = (11, 27),
+    ICON_BORDER_RECT            = (12, 27),
+    ICON_BORDER_LASSO           = (13, 27),
+    ICON_FREEZE                 = (14, 27),
+    ICON_STYLUS_PRESSURE        = (15, 27),
+    ICON_GHOST_DISABLED         = (16, 27),
+    ICON_NEW                    = (17, 27),
+    ICON_FILE_TICK              = (18, 27),
+    ICON_QUIT                   = (19, 27),
+    ICON_URL                    = (20, 27),
+    ICON_RECOVER_LAST           = (21, 27),
+    ICON_FULLSCREEN_ENTER       = (23, 27),
+    ICON_FULLSCREEN_EXIT        = (24, 27),
+    ICON_FULLSCREEN             = ( 0, 28),
+    ICON_SPLITSCREEN            = ( 1, 28),
+    ICON_RIGHTARROW_THIN        = ( 2, 28),
+    ICON_BORDERMOVE             = ( 3, 28),
+    ICON_VIEWZOOM               = ( 4, 28),
+    ICON_ZOOMIN                 = ( 5, 28),
+    ICON_ZOOMOUT                = ( 6, 28),
+    ICON_PANEL_CLOSE            = ( 7, 28),
+    ICON_COPY_ID                = ( 8, 28),
+    ICON_EYEDROPPER             = ( 9, 28),
+    ICON_LINK_AREA              = (10, 28),
+    ICON_AUTO                   = (11, 28),
+    ICON_CHECKBOX_DEHLT         = (12, 28),
+    ICON_CHECKBOX_HLT           = (13, 28),
+    ICON_UNLOCKED               = (14, 28),
+    ICON_LOCKED                 = (15, 28),
+    ICON_UNPINNED               = (16, 28),
+    ICON_PINNED                 = (17, 28),
+    ICON_SCREEN_BACK            = (18, 28),
+    ICON_RIGHTARROW             = (19, 28),
+    ICON_DOWNARROW_HLT          = (20, 28),
+    ICON_DOTSUP                 = (21, 28),
+    ICON_DOTSDOWN               = (22, 28),
+    ICON_LINK                   = (23, 28),
+    ICON_INLINK                 = (24, 28),
+    ICON_PLUGIN                 = (25, 28),
+    ICON_QUESTION               = ( 1, 29),
+    ICON_ERROR                  = ( 2, 29),
+    ICON_CANCEL                 = ( 3, 29),
+    ICON_TRIA_RIGHT             = ( 4, 29),
+    ICON_TRIA_DOWN              = ( 5, 29),
+    ICON_TRIA_LEFT              = ( 6, 29),
+    ICON_TRIA_UP                = ( 7, 29),
+    ICON_ARROW_LEFTRIGHT        = ( 8, 29),
+    ICON_PLUS                   = ( 9, 29),
+    ICON_DISCLOSURE_TRI_DOWN    = (10, 29),
+    ICON_DISCLOSURE_TRI_RIGHT   = (11, 29),
+    ICON_RADIOBUT_OFF           = (12, 29),
+    ICON_RADIOBUT_ON            = (13, 29),
+    ICON_MENU_PANEL             = (14, 29),
+    ICON_BLENDER                = (15, 29),
+    ICON_GRIP                   = (16, 29),
+    ICON_DOT                    = (17, 29),
+    ICON_X                      = (19, 29),
+    ICON_GO_LEFT                = (21, 29),
+    ICON_PLUG                   = (22, 29),
+    ICON_UI                     = (23, 29),
+    ICON_NODE                   = (24, 29),
+    ICON_NODE_SEL               = (25, 29)
+)
+
+/// the icon id for a name, as markup and theme files spell it: "FILE_FOLDER"
+/// or "ICON_FILE_FOLDER", in any case
+pub fn icon_by_name(name: &str) -> Option<i32> {
+    let name = strip_prefix(name);
+    ICON_NAMES.iter()
+        .find(|&&(n, _)| strip_prefix(n).eq_ignore_ascii_case(name))
+        .map(|&(_, id)| id)
+}
+
+/// the name of an icon id, without the "ICON_" prefix, to write it back out;
+/// None if it has none
+pub fn icon_name(iconid: i32) -> Option<&'static str> {
+    ICON_NAMES.iter()
+        .find(|&&(_, id)| id == iconid)
+        .map(|&(n, _)| strip_prefix(n))
+}
+
+fn strip_prefix<'a>(name: &'a str) -> &'a str {
+    if name.len() >= 5 && name.slice_to(5).eq_ignore_ascii_case("ICON_") {
+        name.slice_from(5)
+    } else {
+        name
+    }
+}
+
+#[test]
+fn test_icon_by_name() {
+    use draw::iconsheet::icon_id;
+    assert_eq!(ICON_FILE_FOLDER, icon_id(17, 3));
+    assert_eq!(ICON_MESH_CUBE, icon_id(1, 18));
+    assert_eq!(icon_by_name("MESH_CUBE"), Some(ICON_MESH_CUBE));
+    assert_eq!(icon_by_name("ICON_MESH_CUBE"), Some(ICON_MESH_CUBE));
+    assert_eq!(icon_by_name("mesh_cube"), Some(ICON_MESH_CUBE));
+    assert_eq!(icon_by_name("NO_SUCH_ICON"), None);
+    assert_eq!(icon_name(ICON_FILE_BLANK), Some("FILE_BLANK"));
+    assert_eq!(icon_name(icon_id(0, 4)), None);
+}
+
+// which cells of the sheet have an icon in them, row by row
+#[cfg(test)]
+static SHEET: &'static [&'static str] = &[
+    "######...#####............",
+    "########....#######.......",
+    "########.....####.......##",
+    "##########################",
+    "..........................",
+    "#####.#..##.#########.....",
+    "####....#########.........",
+    "############.#######......",
+    "###..######.##########.###",
+    "####......................",
+    "##########.###############",
+    "##############............",
+    "##########################",
+    "..........................",
+    "..........................",
+    "###.......................",
+    "#############.............",
+    "######...#####.....###....",
+    "##########..#####..######.",
+    "############..............",
+    "###########........######.",
+    "..........................",
+    "###########.####..####..##",
+    "#########################.",
+    "##########................",
+    "#####################.....",
+    "##############............",
+    "######################.##.",
+    "##########################",
+    ".#################.#######",
+];
+
+#[test]
+fn test_no_name_for_an_empty_cell() {
+    for &(name, id) in ICON_NAMES.iter() {
+        let (x, y) = ((id & 0xff) as uint, (id >> 8) as uint);
+        assert!(SHEET[y].as_bytes()[x] == b'#',
+                "{} names the empty cell ({}, {})", name, x, y);
+    }
+}
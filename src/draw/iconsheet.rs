@@ -17,7 +17,7 @@ pub static ICON_SHEET_RES: u32 = 16;
 
 // build an icon ID from two coordinates into the icon sheet, where
 // (0,0) designates the upper-leftmost icon, (1,0) the one right next to it,
-// and so on.
+// and so on. blender_icons has names for them.
 pub fn ICONID(x: u8, y: u8) -> u16 { x as u16 | (y as u16 << 8) }
 
 
@@ -6,6 +6,7 @@ extern crate nanovg;
 
 
 pub mod geom;
+pub mod blender_icons;
 pub mod icons;
 pub mod iconsheet;
 pub mod lowlevel;
@@ -11,6 +11,7 @@
 
 extern crate libc;
 extern crate nanovg;
+extern crate serialize;
 
 extern crate debug;
 #[phase(plugin, link)]
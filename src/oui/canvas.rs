@@ -0,0 +1,108 @@
+use util::{clamp, min};
+use super::geom::{Vec2, Rect};
+
+/// how much one notch of the wheel zooms
+pub static ZOOM_STEP: f32 = 1.2;
+pub static DEFAULT_MIN_ZOOM: f32 = 0.1;
+pub static DEFAULT_MAX_ZOOM: f32 = 10.0;
+
+/// the view of an infinite canvas: a canvas point p shows at pan + p*zoom,
+/// relative to the canvas item's top left. the children of a canvas item
+/// are laid out in canvas points; see Context::set_canvas.
+#[deriving(PartialEq, Show)]
+pub struct CanvasView {
+    pub pan: Vec2,
+    pub zoom: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+}
+
+impl CanvasView {
+    pub fn new() -> CanvasView {
+        CanvasView {
+            pan: Vec2::zero(),
+            zoom: 1.0,
+            min_zoom: DEFAULT_MIN_ZOOM,
+            max_zoom: DEFAULT_MAX_ZOOM,
+        }
+    }
+
+    /// the canvas point under 'p', relative to the item's top left
+    pub fn to_canvas(&self, p: Vec2) -> Vec2 {
+        Vec2 { x: (p.x - self.pan.x) / self.zoom, y: (p.y - self.pan.y) / self.zoom }
+    }
+
+    /// where canvas point 'p' shows, relative to the item's top left
+    pub fn to_view(&self, p: Vec2) -> Vec2 {
+        Vec2 { x: self.pan.x + p.x * self.zoom, y: self.pan.y + p.y * self.zoom }
+    }
+
+    /// the zoom stays within min..max from now on
+    pub fn set_zoom_limits(&mut self, min: f32, max: f32) {
+        assert!(min > 0.0 && min <= max);
+        self.min_zoom = min;
+        self.max_zoom = max;
+        let at = Vec2::zero();
+        self.zoom_at(at, 1.0);
+    }
+
+    /// zoom by 'factor', within the limits, keeping the canvas point under
+    /// 'at' (relative to the item's top left) where it is
+    pub fn zoom_at(&mut self, at: Vec2, factor: f32) {
+        let p = self.to_canvas(at);
+        self.zoom = clamp(self.zoom * factor, self.min_zoom, self.max_zoom);
+        self.pan.x = at.x - p.x * self.zoom;
+        self.pan.y = at.y - p.y * self.zoom;
+    }
+
+    pub fn pan_by(&mut self, dx: f32, dy: f32) {
+        self.pan.x += dx;
+        self.pan.y += dy;
+    }
+
+    /// zoom and pan so that 'content' (in canvas points) fills a view of
+    /// 'size', less 'margin' on each side, and is centered in it
+    pub fn fit(&mut self, size: Vec2, content: Rect, margin: f32) {
+        let w = size.x - 2.0*margin;
+        let h = size.y - 2.0*margin;
+        let zoom = if content.w > 0.0 && content.h > 0.0 {
+            min(w / content.w, h / content.h)
+        } else if content.w > 0.0 {
+            w / content.w
+        } else if content.h > 0.0 {
+            h / content.h
+        } else {
+            1.0
+        };
+        self.zoom = clamp(zoom, self.min_zoom, self.max_zoom);
+        self.pan.x = size.x*0.5 - (content.x + content.w*0.5) * self.zoom;
+        self.pan.y = size.y*0.5 - (content.y + content.h*0.5) * self.zoom;
+    }
+}
+
+#[test]
+fn test_zoom_keeps_the_point_under_the_cursor() {
+    let mut view = CanvasView::new();
+    view.pan_by(10.0, 20.0);
+    let at = Vec2 { x: 100.0, y: 50.0 };
+    let before = view.to_canvas(at);
+    view.zoom_at(at, 2.0);
+    assert_eq!(view.zoom, 2.0);
+    assert_eq!(view.to_canvas(at), before);
+    // no further than the limit
+    view.set_zoom_limits(0.5, 4.0);
+    view.zoom_at(at, 10.0);
+    assert_eq!(view.zoom, 4.0);
+    assert_eq!(view.to_canvas(at), before);
+}
+
+#[test]
+fn test_fit_centers_the_content() {
+    let mut view = CanvasView::new();
+    let content = Rect { x: 100.0, y: 100.0, w: 200.0, h: 50.0 };
+    view.fit(Vec2 { x: 420.0, y: 300.0 }, content, 10.0);
+    // the width decides: 400 for 200
+    assert_eq!(view.zoom, 2.0);
+    assert_eq!(view.to_view(Vec2 { x: 100.0, y: 100.0 }), Vec2 { x: 10.0, y: 100.0 });
+    assert_eq!(view.to_view(Vec2 { x: 300.0, y: 150.0 }), Vec2 { x: 410.0, y: 200.0 });
+}
@@ -1,28 +1,84 @@
+use std::collections::{HashMap, HashSet};
+use std::any::Any;
+use std::mem;
+use std::rc::Rc;
+use std::cell::Cell;
 
 use {
     ItemState,
         COLD,HOT,ACTIVE,FROZEN,
 };
-use util::{max};
+use util::{max, min, clamp};
 use oui::geom::{Vec2, Rect};
 use oui::item::{Item, ItemImp};
+use oui::canvas::{CanvasView, ZOOM_STEP};
+use oui::transition::Transition;
+use oui::timer::{Timer, TimerId};
+use oui::tooltip;
+use oui::tooltip::{Tooltip, TooltipTracker};
+use oui::undo::{Command, UndoStack};
+use oui::property::{Property, ChangeFlag};
+use oui::touch::{
+    TouchPhase,
+        Began, Moved, Ended, Cancelled,
+    TouchPoint,
+    Gesture,
+    GestureRecognizer,
+        Tap, LongPress, Pan, Pinch,
+};
 use oui::{
     Tag,
     Handler,
     EventFlags,
         BUTTON0_UP, BUTTON0_DOWN, BUTTON0_HOT_UP,
         BUTTON0_CAPTURE,
-        APPEND,
+        APPEND, TIMER, DISMISS,
+        DRAG_START, DRAG_END, DRAG_ENTER, DRAG_OVER, DRAG_LEAVE, DROP,
+        TAP, LONG_PRESS, PAN, PINCH,
+        KEY_DOWN, KEY_UP, CHAR, FOCUS, BLUR,
+        ENTER, LEAVE, CONTEXT_MENU, WHEEL,
+    KeyMods,
+    Key,
     LayoutFlags,
         LEFT, RIGHT, HFILL,
 };
 
+// visited-flag bit marking an anchored item as placed, in this layout
+static ANCHOR_PLACED: i32 = 16;
+
 
 enum MouseCapture {
     IDLE,
     CAPTURE,
 }
 
+// a drag-and-drop in progress
+struct Drag {
+    source: Item,
+    payload: Box<Any>,
+    // the drop target under the cursor, if any
+    target: Item,
+    // whether the target would take the payload
+    accepted: bool,
+}
+
+// an entry in the modal stack: the subtree that input is confined to,
+// and the state to return to when it's popped
+struct Modal {
+    root: Item,
+    dismissable: bool,
+    saved_hot: Item,
+    saved_active: Item,
+    saved_active_rect: Rect,
+    saved_capture: MouseCapture,
+}
+
+// keyboard input waiting for process()
+enum KeyInput {
+    KeyPress(Key, KeyMods, bool),
+    CharInput(char),
+}
+
 
 /// this 'Context' is really "Container with Layout"
 /// (in self/smalltalk Morphic there's a World, root container)
@@ -52,6 +108,10 @@ pub struct Context<Wgt> {
     last_cursor: Vec2,
     // where the cursor is currently
     cursor: Vec2,
+    // how far the wheel has turned since the last process()
+    wheel: Vec2,
+    // the canvas being dragged with button 2, if any
+    panning: Item,
 
     hot_tag: Tag,
     active_tag: Tag,
@@ -62,6 +122,74 @@ pub struct Context<Wgt> {
 
     capture: MouseCapture,
 
+    // dialogs and popups; the top one receives all input
+    modals: Vec<Modal>,
+
+    // the item that receives keys and characters
+    focus_item: Item,
+    // modifier keys currently held
+    modifiers: KeyMods,
+    // keys and characters given since the last process(), in order
+    key_input: Vec<KeyInput>,
+    // the key or character being delivered
+    key: Option<Key>,
+    character: Option<char>,
+
+    // fingers currently down
+    touches: Vec<TouchPoint>,
+    // the finger that drives the cursor and button 0
+    primary_touch: Option<u64>,
+    gestures: GestureRecognizer,
+    // the gesture being delivered
+    gesture: Option<Gesture>,
+
+    drag: Option<Drag>,
+    // distance the cursor must move, with button 0 held, to start a drag
+    drag_threshold: f32,
+    // the active item didn't start a drag when asked; don't ask again
+    // until the button is released
+    drag_declined: bool,
+
+    // global UI scale, applied to sizes and margins during layout
+    scale: f32,
+    // round computed rects to whole pixels after layout
+    pixel_snap: bool,
+
+    // current time in seconds, as given by the host
+    time: f64,
+    // display rects of animated items, by tag; kept across clear()
+    // so that a rebuilt UI eases from where the old one was drawn
+    transitions: HashMap<Tag, Transition>,
+
+    // pending timers, in no particular order
+    timers: Vec<Timer>,
+    next_timer_id: u32,
+    // counts clear()s, so that what was due before one isn't delivered
+    // to the items built after it
+    generation: u32,
+    // the timer whose TIMER event is being delivered
+    fired_timer: Option<TimerId>,
+
+    // something visible has changed since the host last drew
+    redraw: bool,
+
+    // value changes made through the ui; kept across clear()
+    history: UndoStack,
+
+    // raised by watched properties when they change. clear() replaces
+    // them, which unsubscribes the old items' watchers.
+    changed: Rc<Cell<bool>>,
+    relayout: Rc<Cell<bool>>,
+    // items frozen while their property is true
+    frozen_bindings: Vec<(Item, Property<bool>)>,
+    // items scrolled vertically by their property
+    scroll_bindings: Vec<(Item, Property<f32>)>,
+    // (item, menu): what a right-click on the item opens
+    context_menus: Vec<(Item, Item)>,
+    // items' tooltips, and when to show them
+    tooltips: Vec<(Item, Tooltip)>,
+    tooltip: TooltipTracker,
+
     items: Vec<ItemImp<Wgt>>,
 }
 
@@ -81,6 +209,8 @@ impl<Wgt> Context<Wgt> {
             last_cursor: Vec2::zero(),
             // where the cursor is currently
             cursor: Vec2::zero(),
+            wheel: Vec2::zero(),
+            panning: Item::none(),
 
             hot_tag: -1,
             active_tag: -1,
@@ -91,10 +221,89 @@ impl<Wgt> Context<Wgt> {
 
             capture: IDLE,
 
+            modals: Vec::new(),
+
+            focus_item: Item::none(),
+            modifiers: KeyMods::empty(),
+            key_input: Vec::new(),
+            key: None,
+            character: None,
+
+            touches: Vec::new(),
+            primary_touch: None,
+            gestures: GestureRecognizer::new(),
+            gesture: None,
+
+            drag: None,
+            drag_threshold: 4.0,
+            drag_declined: false,
+
+            scale: 1.0,
+            pixel_snap: true,
+
+            time: 0.0,
+            transitions: HashMap::new(),
+
+            timers: Vec::new(),
+            next_timer_id: 0,
+            generation: 0,
+            fired_timer: None,
+
+            redraw: true,
+
+            history: UndoStack::new(),
+
+            changed: Rc::new(Cell::new(false)),
+            relayout: Rc::new(Cell::new(false)),
+            frozen_bindings: Vec::new(),
+            scroll_bindings: Vec::new(),
+            context_menus: Vec::new(),
+            tooltips: Vec::new(),
+            tooltip: TooltipTracker::new(tooltip::DEFAULT_DELAY),
+
             items: Vec::new(),
         }
     }
 
+    /// apply an app-defined command and record it for undo
+    pub fn do_command(&mut self, cmd: Box<Command+'static>) {
+        let mut cmd = cmd;
+        cmd.redo();
+        self.push_command(cmd);
+    }
+
+    /// record a change that has already been made, e.g. by a widget
+    pub fn push_command(&mut self, cmd: Box<Command+'static>) {
+        self.history.push(cmd);
+        self.redraw = true;
+    }
+
+    /// take back the last change; false if there's nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let done = self.history.undo();
+        if done { self.redraw = true; }
+        done
+    }
+
+    /// make the last undone change again; false if there's nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let done = self.history.redo();
+        if done { self.redraw = true; }
+        done
+    }
+
+    pub fn can_undo(&self) -> bool { self.history.can_undo() }
+    pub fn can_redo(&self) -> bool { self.history.can_redo() }
+
+    /// how many changes undo() can take back
+    pub fn set_undo_limit(&mut self, limit: uint) {
+        self.history.set_limit(limit);
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
     pub fn set_button(&mut self, button: u64, enabled: bool) {
         let mask = 1u64<<button as uint;
         // set new bit
@@ -119,11 +328,123 @@ impl<Wgt> Context<Wgt> {
         self.get_last_button(button) && !self.get_button(button)
     }
 
-    pub fn set_cursor(&mut self, x: i32, y: i32) {
+    pub fn set_cursor(&mut self, x: f32, y: f32) {
         self.cursor.x = x;
         self.cursor.y = y;
     }
 
+    /// feed wheel motion, in notches (positive y is away from the user);
+    /// it adds up until the next process()
+    pub fn set_wheel(&mut self, dx: f32, dy: f32) {
+        self.wheel.x += dx;
+        self.wheel.y += dy;
+    }
+
+    /// the wheel motion being delivered
+    pub fn get_wheel(&self) -> Vec2 {
+        self.wheel
+    }
+
+    /// feed a touch point. the first finger down also drives the cursor and
+    /// button 0, so that touch works with items made for the mouse; all
+    /// fingers together are fed to the gesture recognizers, whose
+    /// gestures are delivered by process().
+    pub fn set_touch(&mut self, id: u64, x: f32, y: f32, phase: TouchPhase) {
+        let pt = TouchPoint { id: id, x: x, y: y, phase: phase };
+        let now = self.time;
+        self.gestures.touch(now, pt);
+
+        match phase {
+            Began => self.touches.push(pt),
+            Moved => {
+                for t in self.touches.iter_mut() {
+                    if t.id == id { *t = pt; }
+                }
+            }
+            Ended | Cancelled => self.touches.retain(|t| t.id != id),
+        }
+
+        if phase == Began && self.primary_touch.is_none() {
+            self.primary_touch = Some(id);
+        }
+        if self.primary_touch == Some(id) {
+            self.set_cursor(x, y);
+            match phase {
+                Began | Moved => self.set_button(0, true),
+                Ended | Cancelled => {
+                    self.set_button(0, false);
+                    self.primary_touch = None;
+                }
+            }
+        }
+    }
+
+    /// the fingers currently down
+    pub fn get_touches(&self) -> &[TouchPoint] {
+        self.touches.as_slice()
+    }
+
+    /// while a TAP, LONG_PRESS, PAN or PINCH is being handled, its details
+    pub fn get_gesture(&self) -> Option<Gesture> {
+        self.gesture
+    }
+
+    /// to adjust the gesture timings and distances
+    pub fn get_gesture_recognizer(&mut self) -> &mut GestureRecognizer {
+        &mut self.gestures
+    }
+
+    /// a key went down ('pressed') or up; it's sent to the focused item
+    /// as KEY_DOWN or KEY_UP by the next process()
+    pub fn set_key(&mut self, key: Key, mods: KeyMods, pressed: bool) {
+        self.modifiers = mods;
+        self.key_input.push(KeyPress(key, mods, pressed));
+    }
+
+    /// a character was typed; it's sent to the focused item as CHAR by the
+    /// next process()
+    pub fn set_char(&mut self, c: char) {
+        self.key_input.push(CharInput(c));
+    }
+
+    /// the modifier keys held, e.g. for shift-clicks
+    pub fn set_modifiers(&mut self, mods: KeyMods) {
+        self.modifiers = mods;
+    }
+
+    pub fn get_modifiers(&self) -> KeyMods {
+        self.modifiers
+    }
+
+    /// while a KEY_DOWN or KEY_UP is being handled, the key
+    pub fn get_key(&self) -> Option<Key> {
+        self.key
+    }
+
+    /// while a CHAR is being handled, the character
+    pub fn get_char(&self) -> Option<char> {
+        self.character
+    }
+
+    /// give the keyboard focus to 'item' (or to nobody, with Item::none()).
+    /// the item losing it gets BLUR, the one gaining it FOCUS.
+    pub fn set_focus(&mut self, item: Item) {
+        let old = self.focus_item;
+        if old == item { return; }
+        self.focus_item = item;
+        if old.valid() {
+            self.notify_item(old, BLUR);
+        }
+        if item.valid() {
+            self.notify_item(item, FOCUS);
+        }
+        self.redraw = true;
+    }
+
+    pub fn get_focus(&self) -> Item {
+        self.focus_item
+    }
+
     pub fn get_cursor(&self) -> Vec2 {
         self.cursor
     }
@@ -146,6 +467,171 @@ impl<Wgt> Context<Wgt> {
         }
     }
 
+    /// set the UI scale factor (e.g. 1.25 or 1.5 for HiDPI displays);
+    /// item sizes and margins are multiplied by it on the next layout().
+    pub fn set_scale(&mut self, scale: f32) {
+        assert!(scale > 0.0);
+        self.scale = scale;
+    }
+
+    pub fn get_scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// when enabled (the default), layout() rounds every computed rect to
+    /// whole pixels so that strokes stay crisp.
+    pub fn set_pixel_snap(&mut self, enable: bool) {
+        self.pixel_snap = enable;
+    }
+
+    pub fn get_pixel_snap(&self) -> bool {
+        self.pixel_snap
+    }
+
+    /// advance the clock that drives transitions (seconds, monotonic)
+    pub fn set_time(&mut self, t: f64) {
+        self.time = t;
+    }
+
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+
+    /// note that something visible has changed; handlers should call this
+    /// when they change the value a widget displays.
+    pub fn request_redraw(&mut self) {
+        self.redraw = true;
+    }
+
+    /// true if the UI looks different than when it was last drawn:
+    /// hot/active items changed, a handler requested a redraw, a timer
+    /// fired, or a transition is running. hosts can skip drawing otherwise.
+    pub fn needs_redraw(&self) -> bool {
+        self.redraw || self.changed.get() || self.is_animating()
+    }
+
+    /// tell the context that the host has drawn the current state
+    pub fn mark_drawn(&mut self) {
+        self.redraw = false;
+        self.changed.set(false);
+    }
+
+    /// redraw whenever 'prop' changes, from the ui or the app
+    pub fn watch<T: Clone+PartialEq+'static>(&mut self, prop: &Property<T>) {
+        prop.subscribe(box ChangeFlag { flag: self.changed.downgrade() });
+    }
+
+    /// relayout whenever 'prop' changes; see needs_layout()
+    pub fn watch_layout<T: Clone+PartialEq+'static>(&mut self, prop: &Property<T>) {
+        prop.subscribe(box ChangeFlag { flag: self.relayout.downgrade() });
+    }
+
+    /// a property watched with watch_layout() has changed since the last
+    /// layout(); the host should call layout() before drawing
+    pub fn needs_layout(&self) -> bool {
+        self.relayout.get()
+    }
+
+    /// note that an item's size has changed; needs_layout() says so until
+    /// the next layout()
+    pub fn request_layout(&mut self) {
+        self.relayout.set(true);
+    }
+
+    /// keep 'item' frozen while 'prop' is true
+    pub fn bind_frozen(&mut self, item: Item, prop: &Property<bool>) {
+        self.get(item).frozen = prop.get();
+        self.watch(prop);
+        self.frozen_bindings.push((item, prop.clone()));
+    }
+
+    // bring bound state up to date with the properties
+    fn sync_bindings(&mut self) {
+        for i in range(0, self.frozen_bindings.len()) {
+            let (item, frozen) = {
+                let (item, ref prop) = self.frozen_bindings[i];
+                (item, prop.get())
+            };
+            self.get(item).frozen = frozen;
+        }
+        for i in range(0, self.scroll_bindings.len()) {
+            let (item, offset) = {
+                let (item, ref prop) = self.scroll_bindings[i];
+                (item, prop.get())
+            };
+            self.get(item).scroll.y = clamp(offset, 0.0, 1.0);
+        }
+    }
+
+    /// scroll 'item' vertically by 'prop' (see set_scroll); it's laid out
+    /// again when the property changes
+    pub fn bind_scroll(&mut self, item: Item, prop: &Property<f32>) {
+        self.get(item).scroll.y = clamp(prop.get(), 0.0, 1.0);
+        self.watch_layout(prop);
+        self.scroll_bindings.push((item, prop.clone()));
+    }
+
+    /// a right-click (button 1) on item, or on anything inside it without
+    /// a context menu of its own, sends CONTEXT_MENU to 'menu', which is
+    /// expected to open itself at the cursor. Item::none() removes it.
+    pub fn set_context_menu(&mut self, item: Item, menu: Item) {
+        self.context_menus.retain(|&(i, _)| i != item);
+        if menu.valid() {
+            self.context_menus.push((item, menu));
+        }
+    }
+
+    pub fn get_context_menu(&self, item: Item) -> Item {
+        match self.context_menus.iter().find(|&&(i, _)| i == item) {
+            Some(&(_, menu)) => menu,
+            None => Item::none()
+        }
+    }
+
+    /// show 'tip' when the cursor rests on item, or on anything inside it
+    /// without a tooltip of its own
+    pub fn set_tooltip(&mut self, item: Item, tip: Tooltip) {
+        self.remove_tooltip(item);
+        self.tooltips.push((item, tip));
+    }
+
+    pub fn remove_tooltip(&mut self, item: Item) {
+        self.tooltips.retain(|&(i, _)| i != item);
+    }
+
+    pub fn get_tooltip(&self, item: Item) -> Option<&Tooltip> {
+        self.tooltips.iter().find(|&&(i, _)| i == item).map(|&(_, ref tip)| tip)
+    }
+
+    /// seconds the cursor has to rest before a tooltip shows
+    pub fn set_tooltip_delay(&mut self, seconds: f64) {
+        self.tooltip.delay = seconds;
+    }
+
+    /// the item whose tooltip should be drawn now, or none
+    pub fn get_tooltip_item(&self) -> Item {
+        self.tooltip.shown()
+    }
+
+    /// the cursor position the shown tooltip belongs at
+    pub fn get_tooltip_pos(&self) -> Vec2 {
+        self.tooltip.position()
+    }
+
+    /// when the host next has to run process() even without new input:
+    /// now, if a redraw is pending (or animating); else when the next timer
+    /// is due; None if the UI is idle, and can wait for input indefinitely.
+    pub fn next_wakeup(&self) -> Option<f64> {
+        if self.needs_redraw() { return Some(self.time); }
+        let deadlines = [self.next_timer_due(), self.gestures.next_deadline(),
+            self.tooltip.next_deadline()];
+        deadlines.iter().fold(None, |next, &due| match (next, due) {
+            (Some(a), Some(b)) => Some(if a < b {a} else {b}),
+            (Some(a), None) => Some(a),
+            (None, b) => b
+        })
+    }
+
     pub fn root(&mut self) -> Item {
         if self.count() == 0 { return Item::none() }
         Item::wrap(0)
@@ -165,8 +651,23 @@ impl<Wgt> Context<Wgt> {
     /// discard everything, reset to initial empty state
     pub fn clear(&mut self) {
         self.items.clear();
+        self.timers.clear();
+        self.generation += 1;
+        self.modals.clear();
+        self.drag = None;
+        self.changed = Rc::new(Cell::new(false));
+        self.relayout = Rc::new(Cell::new(false));
+        self.frozen_bindings.clear();
+        self.scroll_bindings.clear();
+        self.context_menus.clear();
+        self.tooltips.clear();
+        self.tooltip.reset();
+        self.focus_item = Item::none();
+        self.key_input.clear();
+        self.redraw = true;
         self.hot_item = Item::none();
         self.active_item = Item::none();
+        self.panning = Item::none();
     }
 
     /// create a UI Item, for given Wgt (take ownership of wgt).
@@ -185,7 +686,23 @@ impl<Wgt> Context<Wgt> {
         if !self.get(item).event_flags.contains(event) { return; }
         let handler = self.get(item).handler;
         if handler.is_some() {
+            // inside a canvas, the handler gets the cursor in the
+            // coordinates of get_abs_rect(item), so that it needn't know
+            let canvases = self.canvases_around(item);
+            let saved = (self.cursor, self.last_cursor, self.start_cursor);
+            if canvases.len() > 0 {
+                let (cursor, last, start) = saved;
+                self.cursor = self.map_through(canvases.as_slice(), cursor);
+                self.last_cursor = self.map_through(canvases.as_slice(), last);
+                self.start_cursor = self.map_through(canvases.as_slice(), start);
+            }
             (handler.unwrap())(self, item, event);
+            if canvases.len() > 0 {
+                let (cursor, last, start) = saved;
+                self.cursor = cursor;
+                self.last_cursor = last;
+                self.start_cursor = start;
+            }
         }
     }
 
@@ -219,14 +736,59 @@ impl<Wgt> Context<Wgt> {
         return child;
     }
 
+    /// take 'child' out of its parent, e.g. to close a popup; it and its
+    /// children stay in the context and can be appended again. items are
+    /// only ever freed by clear().
+    pub fn detach(&mut self, child: Item) {
+        let parent = self.parent(child);
+        if parent.invalid() { return; }
+        let (prev, next) = {
+            let pchild = self.get(child);
+            (pchild.previtem, pchild.nextitem)
+        };
+        if prev.valid() { self.get(prev).nextitem = next; }
+        else { self.get(parent).firstkid = next; }
+        if next.valid() { self.get(next).previtem = prev; }
+        else { self.get(parent).lastkid = prev; }
+        self.get(parent).numkids -= 1;
+        // the siblings after it move up one place
+        let mut kid = next;
+        while kid.valid() {
+            self.get(kid).kidid -= 1;
+            kid = self.next_sibling(kid);
+        }
+        {
+            let pchild = self.get(child);
+            pchild.parent = Item::none();
+            pchild.previtem = Item::none();
+            pchild.nextitem = Item::none();
+            pchild.kidid = 0;
+        }
+        // nothing that's gone can stay hot
+        self.hot_item = Item::none();
+        self.redraw = true;
+    }
+
     pub fn set_frozen(&mut self, item: Item, enable: bool) {
         self.get(item).frozen = enable;
+        self.redraw = true;
+    }
+
+    /// true if item or any of its parents is frozen; frozen containers
+    /// freeze what's in them, too
+    pub fn is_frozen(&mut self, item: Item) -> bool {
+        let mut it = item;
+        while it.valid() {
+            if self.get(it).frozen { return true; }
+            it = self.parent(it);
+        }
+        false
     }
 
     pub fn set_size(&mut self, item: Item, w: u32, h: u32) {
         let pitem = self.get(item);
-        pitem.size.x = w as i32;
-        pitem.size.y = h as i32;
+        pitem.size.x = w as f32;
+        pitem.size.y = h as f32;
     }
 
     pub fn get_width(&mut self, item: Item) -> u32 {
@@ -237,6 +799,20 @@ impl<Wgt> Context<Wgt> {
         return self.get(item).size.y as u32;
     }
 
+    /// the size item's content asks for, e.g. the width of its label. unlike
+    /// set_size(), this doesn't fix the size: a fill can stretch the item
+    /// past it, and children needing more space widen it. 0 means no
+    /// preference.
+    pub fn set_preferred_size(&mut self, item: Item, w: u32, h: u32) {
+        let pitem = self.get(item);
+        pitem.preferred.x = w as f32;
+        pitem.preferred.y = h as f32;
+    }
+
+    pub fn get_preferred_size(&mut self, item: Item) -> Vec2 {
+        return self.get(item).preferred;
+    }
+
     pub fn set_layout(&mut self, item: Item, flags: LayoutFlags) {
         self.get(item).layout_flags = flags;
     }
@@ -247,26 +823,240 @@ impl<Wgt> Context<Wgt> {
 
     pub fn set_margins(&mut self, item: Item, l: i32, t: i32, r: i32, b: i32) {
         let pitem = self.get(item);
-        pitem.margins[0] = l;
-        pitem.margins[1] = t;
-        pitem.margins[2] = r;
-        pitem.margins[3] = b;
+        pitem.margins[0] = l as f32;
+        pitem.margins[1] = t as f32;
+        pitem.margins[2] = r as f32;
+        pitem.margins[3] = b as f32;
     }
 
     pub fn get_margin_left(&mut self, item: Item) -> i32 {
-        return self.get(item).margins[0];
+        return self.get(item).margins[0] as i32;
     }
     pub fn get_margin_top(&mut self, item: Item) -> i32 {
-        return self.get(item).margins[1];
+        return self.get(item).margins[1] as i32;
     }
     pub fn get_margin_right(&mut self, item: Item) -> i32 {
-        return self.get(item).margins[2];
+        return self.get(item).margins[2] as i32;
     }
     pub fn get_margin_down(&mut self, item: Item) -> i32 {
-        return self.get(item).margins[3];
+        return self.get(item).margins[3] as i32;
+    }
+
+    /// size item as a fraction (0..1) of its parent's width and/or height;
+    /// the item's margins are taken out of that share. 0 leaves that
+    /// dimension to set_size() and the layout flags.
+    pub fn set_rel_size(&mut self, item: Item, fw: f32, fh: f32) {
+        assert!(fw >= 0.0 && fh >= 0.0);
+        let pitem = self.get(item);
+        pitem.rel_size.x = fw;
+        pitem.rel_size.y = fh;
+    }
+
+    pub fn get_rel_size(&mut self, item: Item) -> Vec2 {
+        return self.get(item).rel_size;
+    }
+
+    /// constrain item to a width:height ratio (e.g. 16.0/9.0 for a thumbnail).
+    /// the height follows the width, unless only the height is fixed, in
+    /// which case the width follows it. 0 removes the constraint.
+    pub fn set_aspect_ratio(&mut self, item: Item, ratio: f32) {
+        assert!(ratio >= 0.0);
+        self.get(item).aspect = ratio;
     }
 
+    pub fn get_aspect_ratio(&mut self, item: Item) -> f32 {
+        return self.get(item).aspect;
+    }
+
+    /// shift item's children (but not its absolute ones) by a fraction
+    /// (0..1) of how far they overflow it; 0 shows their top left, 1
+    /// their bottom right. takes effect at the next layout(). the item
+    /// doesn't clip them; whoever draws it should.
+    pub fn set_scroll(&mut self, item: Item, x: f32, y: f32) {
+        let pitem = self.get(item);
+        pitem.scroll.x = clamp(x, 0.0, 1.0);
+        pitem.scroll.y = clamp(y, 0.0, 1.0);
+    }
+
+    pub fn get_scroll(&mut self, item: Item) -> Vec2 {
+        return self.get(item).scroll;
+    }
+
+    /// size of the bounds of item's children at the last layout, scaled;
+    /// with the item's own size, this gives a scroll handle's size
+    pub fn get_content_size(&mut self, item: Item) -> Vec2 {
+        return self.get(item).extent;
+    }
+
+    /// make item a canvas, or a plain item again. a canvas's children are
+    /// laid out as usual, but in canvas points, which it shows through a
+    /// view that the wheel zooms around the cursor and button 2 pans;
+    /// the children are hit only inside the canvas. whoever draws it
+    /// should clip and transform them the same way.
+    pub fn set_canvas(&mut self, item: Item, enable: bool) {
+        self.get(item).canvas = if enable { Some(CanvasView::new()) } else { None };
+        self.redraw = true;
+    }
+
+    pub fn is_canvas(&mut self, item: Item) -> bool {
+        return self.get(item).canvas.is_some();
+    }
+
+    pub fn get_canvas_view(&mut self, item: Item) -> Option<CanvasView> {
+        return self.get(item).canvas;
+    }
 
+    pub fn set_canvas_view(&mut self, item: Item, view: CanvasView) {
+        assert!(self.is_canvas(item));
+        self.get(item).canvas = Some(view);
+        self.redraw = true;
+    }
+
+    pub fn set_zoom_limits(&mut self, item: Item, min: f32, max: f32) {
+        match self.get(item).canvas {
+            Some(ref mut view) => view.set_zoom_limits(min, max),
+            None => return
+        }
+        self.redraw = true;
+    }
+
+    /// zoom a canvas by 'factor', keeping what's under 'at' (in root
+    /// coordinates, like the cursor) in place
+    pub fn zoom_canvas(&mut self, item: Item, factor: f32, at: Vec2) {
+        let at = self.to_local(item, at);
+        match self.get(item).canvas {
+            Some(ref mut view) => view.zoom_at(at, factor),
+            None => return
+        }
+        self.redraw = true;
+    }
+
+    /// move a canvas's content by (dx, dy) of the canvas's own pixels
+    pub fn pan_canvas(&mut self, item: Item, dx: f32, dy: f32) {
+        match self.get(item).canvas {
+            Some(ref mut view) => view.pan_by(dx, dy),
+            None => return
+        }
+        self.redraw = true;
+    }
+
+    /// zoom and pan a canvas to show all of its children, as of the last
+    /// layout, centered, with 'margin' pixels to spare on each side
+    pub fn fit_canvas(&mut self, item: Item, margin: f32) {
+        let mut bounds: Option<Rect> = None;
+        let mut kid = self.first_child(item);
+        while kid.valid() {
+            let r = self.get(kid).rect;
+            bounds = Some(match bounds {
+                None => r,
+                Some(b) => {
+                    let x = min(b.x, r.x);
+                    let y = min(b.y, r.y);
+                    Rect { x: x, y: y,
+                        w: max(b.x + b.w, r.x + r.w) - x,
+                        h: max(b.y + b.h, r.y + r.h) - y }
+                }
+            });
+            kid = self.next_sibling(kid);
+        }
+        let content = bounds.unwrap_or(Rect::zero());
+        let rect = self.get(item).rect;
+        let size = Vec2 { x: rect.w, y: rect.h };
+        match self.get(item).canvas {
+            Some(ref mut view) => view.fit(size, content, margin),
+            None => return
+        }
+        self.redraw = true;
+    }
+
+    /// 'p', in root coordinates, mapped through the canvases around item
+    /// into the coordinates get_abs_rect(item) is in; outside of canvases,
+    /// 'p' as it is
+    pub fn to_item_space(&mut self, item: Item, p: Vec2) -> Vec2 {
+        let canvases = self.canvases_around(item);
+        self.map_through(canvases.as_slice(), p)
+    }
+
+    // 'p', in root coordinates, relative to item's top left, in the
+    // pixels its canvas view pans by
+    fn to_local(&mut self, item: Item, p: Vec2) -> Vec2 {
+        let p = self.to_item_space(item, p);
+        let rect = self.get_abs_rect(item);
+        Vec2 { x: p.x - rect.x, y: p.y - rect.y }
+    }
+
+    // the canvases item is inside of, outermost first
+    fn canvases_around(&mut self, item: Item) -> Vec<Item> {
+        let mut canvases = Vec::new();
+        let mut it = self.parent(item);
+        while it.valid() {
+            if self.get(it).canvas.is_some() {
+                canvases.insert(0, it);
+            }
+            it = self.parent(it);
+        }
+        canvases
+    }
+
+    fn map_through(&mut self, canvases: &[Item], p: Vec2) -> Vec2 {
+        let mut p = p;
+        for &c in canvases.iter() {
+            // in the space of c's rect, as mapped so far
+            let origin = self.get_abs_rect(c);
+            let view = self.get(c).canvas.unwrap();
+            let q = view.to_canvas(Vec2 { x: p.x - origin.x, y: p.y - origin.y });
+            p = Vec2 { x: origin.x + q.x, y: origin.y + q.y };
+        }
+        p
+    }
+
+
+    /// take item out of its parent's flow layout and place it at (x, y) in
+    /// the parent's coordinates. its size comes from set_size() (or its
+    /// content) as usual, and it doesn't count toward the parent's size.
+    pub fn set_absolute(&mut self, item: Item, x: i32, y: i32) {
+        let pitem = self.get(item);
+        pitem.absolute = true;
+        pitem.anchor = Item::none();
+        pitem.abs_pos.x = x as f32;
+        pitem.abs_pos.y = y as f32;
+    }
+
+    /// take item out of its parent's flow layout and pin it to 'anchor',
+    /// which may be any item in the context (unlike set_rel_to_*, which
+    /// only relate siblings). flags select the edges to align, as for
+    /// set_layout: LEFT/RIGHT/TOP/DOWN align that edge of item with the
+    /// same edge of the anchor, no flag centers it on the anchor.
+    /// (dx, dy) is added as an offset.
+    pub fn set_anchor(&mut self, item: Item, anchor: Item, flags: LayoutFlags,
+        dx: i32, dy: i32
+    ) {
+        assert!(anchor.valid() && anchor != item);
+        let pitem = self.get(item);
+        pitem.absolute = true;
+        pitem.anchor = anchor;
+        pitem.anchor_flags = flags;
+        pitem.abs_pos.x = dx as f32;
+        pitem.abs_pos.y = dy as f32;
+    }
+
+    /// put item back into its parent's flow layout
+    pub fn clear_absolute(&mut self, item: Item) {
+        let pitem = self.get(item);
+        pitem.absolute = false;
+        pitem.anchor = Item::none();
+    }
+
+    pub fn is_absolute(&mut self, item: Item) -> bool {
+        return self.get(item).absolute;
+    }
+
+    pub fn get_anchor(&mut self, item: Item) -> Item {
+        return self.get(item).anchor;
+    }
+
+    // set_rel_to_* link siblings into a flow; to position relative to an
+    // item elsewhere in the hierarchy, use set_anchor.
     pub fn set_rel_to_left(&mut self, item: Item, other: Item) {
         assert!(!other.valid() || (self.parent(other) == self.parent(item)));
         self.get(item).relto[0] = other;
@@ -300,10 +1090,238 @@ impl<Wgt> Context<Wgt> {
         return self.get(item).relto[3];
     }
 
+    /// animate item's rect over 'duration' seconds whenever layout() moves
+    /// or resizes it. the item needs a unique, nonzero tag, by which it's
+    /// recognized across layouts (and across clear() and rebuild); items
+    /// without one, or sharing one, don't animate. 0 turns it off.
+    pub fn set_transition(&mut self, item: Item, duration: f32) {
+        assert!(duration >= 0.0);
+        self.get(item).transition = duration;
+    }
+
+    pub fn get_transition(&mut self, item: Item) -> f32 {
+        return self.get(item).transition;
+    }
+
+    /// while item is in transition, the rect (in root coordinates) it should
+    /// be drawn at; None when it's at rest at get_rect().
+    /// hit testing always uses the final rect.
+    pub fn get_display_rect(&mut self, item: Item) -> Option<Rect> {
+        if self.get(item).transition <= 0.0 { return None; }
+        let tag = self.get(item).tag;
+        let now = self.time;
+        match self.transitions.find(&tag) {
+            Some(tr) if tr.is_running(now) => Some(tr.rect_at(now)),
+            _ => None
+        }
+    }
+
+    /// true if any item is still easing toward its new rect
+    pub fn is_animating(&self) -> bool {
+        let now = self.time;
+        self.transitions.values().any(|tr| tr.is_running(now))
+    }
+
+    /// send item a TIMER event once, 'delay' seconds from now.
+    /// the item's handler must accept TIMER.
+    pub fn set_timer(&mut self, item: Item, delay: f64) -> TimerId {
+        let due = self.time + delay;
+        self.add_timer(item, due, 0.0)
+    }
+
+    /// send item a TIMER event every 'interval' seconds, starting one
+    /// interval from now, until cancelled.
+    pub fn set_interval(&mut self, item: Item, interval: f64) -> TimerId {
+        assert!(interval > 0.0);
+        let due = self.time + interval;
+        self.add_timer(item, due, interval)
+    }
+
+    fn add_timer(&mut self, item: Item, due: f64, interval: f64) -> TimerId {
+        assert!(item.valid());
+        let id = TimerId(self.next_timer_id);
+        self.next_timer_id += 1;
+        self.timers.push(Timer { id: id, item: item, due: due, interval: interval });
+        id
+    }
+
+    pub fn cancel_timer(&mut self, id: TimerId) {
+        self.timers.retain(|t| t.id != id);
+    }
+
+    /// cancel all of item's timers
+    pub fn cancel_timers(&mut self, item: Item) {
+        self.timers.retain(|t| t.item != item);
+    }
+
+    /// while a TIMER event is being handled, the timer that fired
+    pub fn get_fired_timer(&self) -> Option<TimerId> {
+        self.fired_timer
+    }
+
+    /// the time (as given to set_time) at which the next timer is due,
+    /// if any; hosts that block waiting for input should wake up by then.
+    pub fn next_timer_due(&self) -> Option<f64> {
+        self.timers.iter().fold(None, |next, t| match next {
+            Some(due) if due <= t.due => Some(due),
+            _ => Some(t.due)
+        })
+    }
+
     pub fn get_rect(&mut self, item: Item) -> Rect {
         return self.get(item).rect;
     }
 
+    /// confine input to item's subtree (a dialog or popup menu), until
+    /// pop_modal(). clicks outside of it are swallowed; if dismissable, item
+    /// is also sent DISMISS. modals stack: the most recent one has input.
+    pub fn push_modal(&mut self, item: Item, dismissable: bool) {
+        assert!(item.valid());
+        self.modals.push(Modal {
+            root: item,
+            dismissable: dismissable,
+            saved_hot: self.hot_item,
+            saved_active: self.active_item,
+            saved_active_rect: self.active_rect,
+            saved_capture: self.capture,
+        });
+        // nothing beneath the modal stays hot or active
+        self.hot_item = Item::none();
+        self.active_item = Item::none();
+        self.capture = IDLE;
+        self.redraw = true;
+    }
+
+    /// remove the topmost modal, restoring the hot and active items as they
+    /// were when it was pushed. returns the modal's root, or none.
+    pub fn pop_modal(&mut self) -> Item {
+        match self.modals.pop() {
+            None => Item::none(),
+            Some(modal) => {
+                self.hot_item = modal.saved_hot;
+                self.active_item = modal.saved_active;
+                self.active_rect = modal.saved_active_rect;
+                self.capture = modal.saved_capture;
+                // a capture that began before the modal ended while it was up
+                let stale = match self.capture {
+                    CAPTURE => !self.get_button(0),
+                    IDLE => false
+                };
+                if stale {
+                    self.active_item = Item::none();
+                    self.capture = IDLE;
+                }
+                self.redraw = true;
+                modal.root
+            }
+        }
+    }
+
+    /// root of the topmost modal, or none
+    pub fn get_modal(&self) -> Item {
+        match self.modals.last() {
+            Some(modal) => modal.root,
+            None => Item::none()
+        }
+    }
+
+    pub fn get_modal_count(&self) -> uint {
+        self.modals.len()
+    }
+
+    /// distance (unscaled) the cursor has to move with button 0 held before
+    /// the pressed item is sent DRAG_START
+    pub fn set_drag_threshold(&mut self, distance: f32) {
+        self.drag_threshold = distance;
+    }
+
+    /// begin dragging 'payload' from the active item; only meaningful
+    /// while handling DRAG_START. drop targets can inspect the payload
+    /// with get_drag_payload().downcast_ref::<T>().
+    pub fn start_drag(&mut self, payload: Box<Any>) {
+        assert!(self.active_item.valid());
+        self.drag = Some(Drag {
+            source: self.active_item,
+            payload: payload,
+            target: Item::none(),
+            accepted: false,
+        });
+        self.redraw = true;
+    }
+
+    /// called by the drop target under the cursor (on DRAG_ENTER or
+    /// DRAG_OVER) to say whether it would take the payload
+    pub fn accept_drag(&mut self, accept: bool) {
+        match self.drag {
+            Some(ref mut drag) => drag.accepted = accept,
+            None => {}
+        }
+    }
+
+    /// abandon the drag in progress; the target is sent DRAG_LEAVE, the
+    /// source DRAG_END. the press itself continues until button 0 is up.
+    pub fn cancel_drag(&mut self) {
+        let target = self.get_drag_target();
+        if target.valid() {
+            self.notify_item(target, DRAG_LEAVE);
+        }
+        match self.drag {
+            Some(ref mut drag) => { drag.target = Item::none(); drag.accepted = false; }
+            None => return
+        }
+        let source = self.get_drag_source();
+        self.notify_item(source, DRAG_END);
+        self.drag = None;
+        self.drag_declined = true;
+        self.redraw = true;
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    pub fn get_drag_source(&self) -> Item {
+        match self.drag {
+            Some(ref drag) => drag.source,
+            None => Item::none()
+        }
+    }
+
+    pub fn get_drag_target(&self) -> Item {
+        match self.drag {
+            Some(ref drag) => drag.target,
+            None => Item::none()
+        }
+    }
+
+    pub fn is_drag_accepted(&self) -> bool {
+        match self.drag {
+            Some(ref drag) => drag.accepted,
+            None => false
+        }
+    }
+
+    pub fn get_drag_payload(&self) -> Option<&Any> {
+        match self.drag {
+            Some(ref drag) => Some(&*drag.payload),
+            None => None
+        }
+    }
+
+    /// item's rect in root coordinates; inside a canvas, in its canvas
+    /// points, offset by where the canvas is. see to_item_space.
+    pub fn get_abs_rect(&mut self, item: Item) -> Rect {
+        let mut rect = self.get(item).rect;
+        let mut it = self.parent(item);
+        while it.valid() {
+            let prect = self.get(it).rect;
+            rect.x += prect.x;
+            rect.y += prect.y;
+            it = self.parent(it);
+        }
+        rect
+    }
+
     pub fn get_active_rect(&self) -> Rect {
         return self.active_rect;
     }
@@ -372,7 +1390,7 @@ impl<Wgt> Context<Wgt> {
         return self.get(item).numkids;
     }
 
-    pub fn find_item(&mut self, item: Item, x: i32, y: i32, ox: i32, oy: i32) -> Item {
+    pub fn find_item(&mut self, item: Item, x: f32, y: f32, ox: f32, oy: f32) -> Item {
         let mut rect = {
             let pitem = self.get(item);
             if pitem.frozen { return Item::none(); }
@@ -382,14 +1400,36 @@ impl<Wgt> Context<Wgt> {
         let y = y - rect.y;
         let ox = ox + rect.x;
         let oy = oy + rect.y;
-        if (x>=0)
-        && (y>=0)
-        && (x<rect.w)
-        && (y<rect.h) {
+        let inside = (x>=0.0)
+            && (y>=0.0)
+            && (x<rect.w)
+            && (y<rect.h);
+        // a canvas's children are in canvas points, and only show inside it
+        let (kx, ky) = match self.get(item).canvas {
+            Some(view) => {
+                if !inside { return Item::none(); }
+                let p = view.to_canvas(Vec2 { x: x, y: y });
+                (p.x, p.y)
+            }
+            None => (x, y)
+        };
+        // absolute children float above their siblings, and may lie
+        // outside their parent, so they're tested first, and always
+        let mut kid = self.first_child(item);
+        while kid.valid() {
+            if self.get(kid).absolute {
+                let best_hit = self.find_item(kid,kx,ky,ox,oy);
+                if best_hit.valid() { return best_hit; }
+            }
+            kid = self.next_sibling(kid);
+        }
+        if inside {
             let mut kid = self.first_child(item);
             while kid.valid() {
-                let best_hit = self.find_item(kid,x,y,ox,oy);
-                if best_hit.valid() { return best_hit; }
+                if !self.get(kid).absolute {
+                    let best_hit = self.find_item(kid,kx,ky,ox,oy);
+                    if best_hit.valid() { return best_hit; }
+                }
                 kid = self.next_sibling(kid);
             }
             rect.x += ox;
@@ -431,16 +1471,36 @@ impl<Wgt> Context<Wgt> {
     ///////////////////////////////////////////////////////////////////
     // Internals
 
+    /// margin of item on side i (0..4), in scaled units
+    fn margin(&mut self, item: Item, i: uint) -> f32 {
+        let scale = self.scale;
+        self.get(item).margins[i] * scale
+    }
+
+    /// true if item's extent in dim comes from its own settings (absolute
+    /// size, fraction of parent, or aspect ratio) rather than from being
+    /// stretched by its parent
+    fn is_fixed_size(&mut self, item: Item, dim: uint) -> bool {
+        let pitem = self.get(item);
+        if pitem.size[dim] > 0.0 || pitem.rel_size[dim] > 0.0 { return true; }
+        if pitem.aspect > 0.0 {
+            // height always follows width; width only follows a fixed height
+            return dim == 1 || pitem.size.y > 0.0;
+        }
+        false
+    }
+
     fn compute_chain_size(&mut self, item: Item,
-        need_size: &mut i32, hard_size: &mut i32, dim: uint
+        need_size: &mut f32, hard_size: &mut f32, dim: uint
     ) {
         let wdim = dim+2;
+        let scale = self.scale;
         let mut size = {
             let pitem = self.get(item);
-            pitem.rect[wdim] + pitem.margins[dim] + pitem.margins[wdim]
+            pitem.rect[wdim] + (pitem.margins[dim] + pitem.margins[wdim])*scale
         };
         *need_size = size;
-        *hard_size = if self.get(item).size[dim] > 0 {size} else {0};
+        *hard_size = if self.is_fixed_size(item, dim) {size} else {0.0};
 
         self.get(item).visited |= 1<<dim;
         // traverse along left neighbors
@@ -450,11 +1510,12 @@ impl<Wgt> Context<Wgt> {
         while ((self.get(prev).layout_flags.bits>>dim) & LEFT.bits) != 0 {
             prev = self.get(prev).relto[dim];
             if prev.invalid() { break };
+            let fixed = self.is_fixed_size(prev, dim);
             let pitem = self.get(prev);
             pitem.visited |= 1<<dim;
-            size = pitem.rect[wdim] + pitem.margins[dim] + pitem.margins[wdim];
+            size = pitem.rect[wdim] + (pitem.margins[dim] + pitem.margins[wdim])*scale;
             *need_size = (*need_size) + size;
-            *hard_size = (*hard_size) + (if pitem.size[dim] > 0 {size} else {0});
+            *hard_size = (*hard_size) + (if fixed {size} else {0.0});
             iter += 1;
             assert!(iter<1000000); // infinite loop
         }
@@ -465,39 +1526,59 @@ impl<Wgt> Context<Wgt> {
         while ((self.get(next).layout_flags.bits>>dim) & RIGHT.bits) != 0 {
             next = self.get(next).relto[wdim];
             if next.invalid() { break };
+            let fixed = self.is_fixed_size(next, dim);
             let pitem = self.get(next);
             pitem.visited |= 1<<dim;    // are we gettin our dim's and wdim's mixed up? idono
-            size = pitem.rect[wdim] + pitem.margins[dim] + pitem.margins[wdim];
+            size = pitem.rect[wdim] + (pitem.margins[dim] + pitem.margins[wdim])*scale;
             *need_size = (*need_size) + size;
-            *hard_size = (*hard_size) + (if pitem.size[dim] > 0 {size} else {0});
+            *hard_size = (*hard_size) + (if fixed {size} else {0.0});
             iter += 1;
             assert!(iter<1000000); // infinite loop
         }
     }
 
-    fn compute_size_dim(&mut self, item: Item, dim: uint) {
-        let wdim = dim+2;
-        let mut need_size = 0;
-        let mut hard_size = 0;
+    /// the space item's children need along dim, as (needed, hard) where
+    /// 'hard' counts only the chains' fixed-size members.
+    fn compute_kids_size(&mut self, item: Item, dim: uint) -> (f32, f32) {
+        let mut need_size = 0.0;
+        let mut hard_size = 0.0;
+        let mut kid = self.get(item).firstkid;
+        while kid.valid() {
+            self.get(kid).visited &= !(1<<dim);
+            kid = self.next_sibling(kid);
+        }
         let mut kid = self.get(item).firstkid;
         while kid.valid() {
             let visited = self.get(kid).visited;
-            if visited & (1<<dim) == 0 {
-                let mut ns: i32 = 0;
-                let mut hs: i32 = 0;
+            // absolute items don't take up space in their parent
+            if visited & (1<<dim) == 0 && !self.get(kid).absolute {
+                let mut ns: f32 = 0.0;
+                let mut hs: f32 = 0.0;
                 self.compute_chain_size(kid, &mut ns, &mut hs, dim);
                 need_size = max(need_size, ns);
                 hard_size = max(hard_size, hs);
             }
             kid = self.next_sibling(kid);
         }
+        (need_size, hard_size)
+    }
+
+    fn compute_size_dim(&mut self, item: Item, dim: uint) {
+        let wdim = dim+2;
+        let scale = self.scale;
+        let (need_size, hard_size) = self.compute_kids_size(item, dim);
         let pitem = self.get(item);
         pitem.computed_size[dim] = hard_size;
 
-        if pitem.size[dim] > 0 {
-            pitem.rect[wdim] = pitem.size[dim];
+        if pitem.size[dim] > 0.0 {
+            pitem.rect[wdim] = pitem.size[dim]*scale;
+        } else if pitem.aspect > 0.0 && dim == 1 {
+            // widths are final by the time heights are computed
+            pitem.rect[wdim] = pitem.rect.w / pitem.aspect;
+        } else if pitem.aspect > 0.0 && pitem.size.y > 0.0 {
+            pitem.rect[wdim] = pitem.size.y*scale*pitem.aspect;
         } else {
-            pitem.rect[wdim] = need_size;
+            pitem.rect[wdim] = max(need_size, pitem.preferred[dim]*scale);
         }
     }
 
@@ -515,17 +1596,27 @@ impl<Wgt> Context<Wgt> {
 
     fn layout_child_item(&mut self, parent: Item, item: Item, dyncount: &mut i32, dim: uint) {
         //let pitem = self.get(item);
+        let scale = self.scale;
 
         if self.get(item).visited & (4<<dim) != 0 {return};
         self.get(item).visited |= 4<<dim;
 
-        if self.get(item).size[dim] == 0 {
+        if self.get(item).absolute {
+            // anchored items are placed once all rects are known
+            let pitem = self.get(item);
+            if pitem.anchor.invalid() {
+                pitem.rect[dim] = pitem.abs_pos[dim]*scale;
+            }
+            return;
+        }
+
+        if !self.is_fixed_size(item, dim) {
             *dyncount = (*dyncount)+1;
         }
 
         let wdim = dim+2;
 
-        let mut x = 0;
+        let mut x = 0.0;
         let mut s = self.get(parent).rect[wdim];
 
         let flags = self.get(item).layout_flags.bits>>dim;
@@ -536,58 +1627,88 @@ impl<Wgt> Context<Wgt> {
         if hasl {
             let l = self.get(item).relto[dim];
             self.layout_child_item(parent, l, dyncount, dim);
+            let ml = self.margin(l, wdim);
             let pl = self.get(l);
-            x = pl.rect[dim]+pl.rect[wdim]+pl.margins[wdim];
+            x = pl.rect[dim]+pl.rect[wdim]+ml;
             s -= x;
         }
         if hasr {
             let r = self.get(item).relto[wdim];
             self.layout_child_item(parent, r, dyncount, dim);
+            let mr = self.margin(r, dim);
             let pr = self.get(r);
-            s = pr.rect[dim]-pr.margins[dim]-x;
+            s = pr.rect[dim]-mr-x;
         }
 
+        // this item's own margins, scaled
+        let m0 = self.margin(item, dim);
+        let m1 = self.margin(item, wdim);
+
         match flags & HFILL {
             LEFT => {
-                self.get(item).rect[dim] = x+self.get(item).margins[dim];
+                self.get(item).rect[dim] = x+m0;
             }
             RIGHT => {
-                self.get(item).rect[dim] = x+s-self.get(item).rect[wdim]-self.get(item).margins[wdim];
+                self.get(item).rect[dim] = x+s-self.get(item).rect[wdim]-m1;
             }
             HFILL => {
-                if self.get(item).size[dim] > 0 { // hard maximum size; can't stretch
+                if self.is_fixed_size(item, dim) { // hard maximum size; can't stretch
                     if !hasl {
-                        self.get(item).rect[dim] = x+self.get(item).margins[dim];
+                        self.get(item).rect[dim] = x+m0;
                     }
                     else {
-                        self.get(item).rect[dim] = x+s-self.get(item).rect[wdim]-self.get(item).margins[wdim];
+                        self.get(item).rect[dim] = x+s-self.get(item).rect[wdim]-m1;
                     }
                 } else {
                     if true { // !self.get(item).rect[wdim]) {
                         let width = self.get(parent).rect[wdim] - self.get(parent).computed_size[dim];
-                        let space = width / (*dyncount);
+                        let space = width / (*dyncount) as f32;
                         //let rest = width - space*(*dyncount);
                         if !hasl {
-                            self.get(item).rect[dim] = x+self.get(item).margins[dim];
-                            self.get(item).rect[wdim] = s-self.get(item).margins[dim]-self.get(item).margins[wdim];
+                            self.get(item).rect[dim] = x+m0;
+                            self.get(item).rect[wdim] = s-m0-m1;
                         } else {
-                            self.get(item).rect[wdim] = space-self.get(item).margins[dim]-self.get(item).margins[wdim];
-                            self.get(item).rect[dim] = x+s-self.get(item).rect[wdim]-self.get(item).margins[wdim];
+                            self.get(item).rect[wdim] = space-m0-m1;
+                            self.get(item).rect[dim] = x+s-self.get(item).rect[wdim]-m1;
                         }
                     } else {
-                        self.get(item).rect[dim] = x+self.get(item).margins[dim];
-                        self.get(item).rect[wdim] = s-self.get(item).margins[dim]-self.get(item).margins[wdim];
+                        self.get(item).rect[dim] = x+m0;
+                        self.get(item).rect[wdim] = s-m0-m1;
                     }
                 }
             }
             //default:
             _ /*HCENTER*/ => {
-                self.get(item).rect[dim] = x+(s-self.get(item).rect[wdim])/2+self.get(item).margins[dim];
+                self.get(item).rect[dim] = x+(s-self.get(item).rect[wdim])/2.0+m0;
             }
         }
     }
 
+    // children sized as a fraction of item can only be resolved now that
+    // item's own rect is known; their share is then taken out of the space
+    // that the flexible siblings divide between them.
+    fn resolve_rel_sizes(&mut self, item: Item, dim: uint) {
+        let wdim = dim+2;
+        let avail = self.get(item).rect[wdim];
+        let mut resolved = false;
+        let mut kid = self.get(item).firstkid;
+        while kid.valid() {
+            let frac = self.get(kid).rel_size[dim];
+            if frac > 0.0 {
+                let margins = self.margin(kid, dim) + self.margin(kid, wdim);
+                self.get(kid).rect[wdim] = max(0.0, frac*avail - margins);
+                resolved = true;
+            }
+            kid = self.next_sibling(kid);
+        }
+        if resolved {
+            let (_, hard_size) = self.compute_kids_size(item, dim);
+            self.get(item).computed_size[dim] = hard_size;
+        }
+    }
+
     fn layout_item_dim(&mut self, item: Item, dim: uint) {
+        self.resolve_rel_sizes(item, dim);
         let mut kid = self.get(item).firstkid;
         while kid.valid() {
             //let pkid = self.get(kid);
@@ -595,6 +1716,36 @@ impl<Wgt> Context<Wgt> {
             self.layout_child_item(item, kid, &mut dyncount, dim);
             kid = self.next_sibling(kid);
         }
+        self.scroll_kids(item, dim);
+    }
+
+    // measure the flowing children's extent, then shift them by the
+    // item's scroll fraction of however far they reach past its end
+    fn scroll_kids(&mut self, item: Item, dim: uint) {
+        let wdim = dim+2;
+        let mut extent = 0.0f32;
+        let mut kid = self.first_child(item);
+        while kid.valid() {
+            if !self.get(kid).absolute {
+                let m = self.margin(kid, wdim);
+                let rect = self.get(kid).rect;
+                extent = max(extent, rect[dim] + rect[wdim] + m);
+            }
+            kid = self.next_sibling(kid);
+        }
+        self.get(item).extent[dim] = extent;
+
+        let overflow = max(0.0, extent - self.get(item).rect[wdim]);
+        let shift = self.get(item).scroll[dim] * overflow;
+        if shift == 0.0 { return; }
+        let mut kid = self.first_child(item);
+        while kid.valid() {
+            if !self.get(kid).absolute {
+                let pos = self.get(kid).rect[dim];
+                self.get(kid).rect[dim] = pos - shift;
+            }
+            kid = self.next_sibling(kid);
+        }
     }
 
     fn layout_item(&mut self, item: Item, dim: uint) {
@@ -609,28 +1760,141 @@ impl<Wgt> Context<Wgt> {
 
     pub fn layout(&mut self) {
         if self.count() == 0 { return; }
+        self.relayout.set(false);
+        self.sync_bindings();
         let root = self.root();
 
         // compute widths
         self.compute_best_size(root,0);
         // position root element rect
-        self.get(root).rect.x = self.get(root).margins[0];
+        self.get(root).rect.x = self.margin(root, 0);
         self.layout_item(root,0);
 
         // compute heights
         self.compute_best_size(root,1);
         // position root element rect
-        self.get(root).rect.y = self.get(root).margins[1];
+        self.get(root).rect.y = self.margin(root, 1);
         self.layout_item(root,1);
+
+        // pin anchored items to their (now final) anchors
+        for i in range(0, self.count()) {
+            self.items.get_mut(i).visited &= !ANCHOR_PLACED;
+        }
+        for i in range(0, self.count()) {
+            let it = Item::wrap(i as i32);
+            if self.get(it).anchor.valid() {
+                self.place_anchored(it);
+            }
+        }
+
+        // round to whole pixels, for crisp strokes
+        if self.pixel_snap {
+            self.snap_item(root);
+        }
+
+        self.update_transitions();
+        self.redraw = true;
+    }
+
+    // start a transition for each animated item whose rect has changed
+    // since the last layout; forget those that are gone.
+    fn update_transitions(&mut self) {
+        let now = self.time;
+        let mut transitions = HashMap::new();
+        // tags seen on more than one animated item: which one a transition
+        // belongs to can't be told, so none of them animate
+        let mut shared = HashSet::new();
+        for i in range(0, self.count()) {
+            let it = Item::wrap(i as i32);
+            let (tag, duration) = {
+                let pitem = self.get(it);
+                (pitem.tag, pitem.transition)
+            };
+            if duration <= 0.0 || tag == 0 || shared.contains(&tag) { continue; }
+            if transitions.contains_key(&tag) {
+                transitions.remove(&tag);
+                shared.insert(tag);
+                continue;
+            }
+            let rect = self.get_abs_rect(it);
+            let tr = match self.transitions.pop(&tag) {
+                Some(old) => {
+                    if old.to == rect { old }
+                    else {
+                        // start from wherever it's drawn now, so that an
+                        // interrupted transition doesn't jump
+                        Transition {
+                            from: old.rect_at(now),
+                            to: rect,
+                            start: now,
+                            duration: duration
+                        }
+                    }
+                }
+                // first appearance, nothing to ease from
+                None => Transition::at(rect)
+            };
+            transitions.insert(tag, tr);
+        }
+        self.transitions = transitions;
+    }
+
+    fn place_anchored(&mut self, item: Item) {
+        if self.get(item).visited & ANCHOR_PLACED != 0 { return; }
+        self.get(item).visited |= ANCHOR_PLACED;
+
+        // the anchor, or a container of it or of us, may be anchored
+        // itself; those have to be in place before we can measure.
+        let anchor = self.get(item).anchor;
+        let mut it = anchor;
+        while it.valid() {
+            if self.get(it).anchor.valid() { self.place_anchored(it); }
+            it = self.parent(it);
+        }
+        let mut it = self.parent(item);
+        while it.valid() {
+            if self.get(it).anchor.valid() { self.place_anchored(it); }
+            it = self.parent(it);
+        }
+
+        let target = self.get_abs_rect(anchor);
+        let parent = self.parent(item);
+        let origin = if parent.valid() { self.get_abs_rect(parent) } else { Rect::zero() };
+        let scale = self.scale;
+        let pitem = self.get(item);
+        for dim in range(0u, 2u) {
+            let wdim = dim+2;
+            let flags = LayoutFlags::from_bits(pitem.anchor_flags.bits>>dim).expect("bitfail");
+            let pos = match flags & HFILL {
+                LEFT | HFILL => target[dim],
+                RIGHT => target[dim]+target[wdim]-pitem.rect[wdim],
+                _ /*HCENTER*/ => target[dim]+(target[wdim]-pitem.rect[wdim])/2.0,
+            };
+            pitem.rect[dim] = pos - origin[dim] + pitem.abs_pos[dim]*scale;
+        }
+    }
+
+    // rects are relative to their parent, so snapping each one in turn
+    // (starting from the root) puts every absolute edge on a whole pixel.
+    fn snap_item(&mut self, item: Item) {
+        self.get(item).rect.snap();
+        let mut kid = self.first_child(item);
+        while kid.valid() {
+            self.snap_item(kid);
+            kid = self.next_sibling(kid);
+        }
     }
 
     pub fn process(&mut self) {
         if self.count() == 0 { return; }
+        self.sync_bindings();
 
         let cursor = self.cursor;
-        let root = self.root();
-        let hot = self.find_item(root, cursor.x, cursor.y, 0, 0);
+        let modal = self.get_modal();
+        let hot = self.hit_test(cursor.x, cursor.y);
         let active = self.active_item;
+        let last_active = self.active_item;
+        let last_hot = self.hot_item;
 
         match self.capture {
             IDLE => {
@@ -639,8 +1903,18 @@ impl<Wgt> Context<Wgt> {
                     self.hot_item = Item::none();
                     self.active_rect = self.hot_rect;
                     self.active_item = hot;
+                    // clicking anywhere else takes the focus away
+                    if self.focus_item.valid() && self.focus_item != hot {
+                        self.set_focus(Item::none());
+                    }
                     if hot.valid() {
                         self.notify_item(hot, BUTTON0_DOWN);
+                    } else if modal.valid() {
+                        // click outside the modal: swallowed, and maybe dismissing
+                        let dismissable = self.modals.last().map_or(false, |m| m.dismissable);
+                        if dismissable {
+                            self.notify_item(modal, DISMISS);
+                        }
                     }
                     self.capture = CAPTURE;
                 } else {
@@ -649,7 +1923,13 @@ impl<Wgt> Context<Wgt> {
             }
             CAPTURE => {
                 if !self.get_button(0) {
-                    if active.valid() {
+                    if self.drag.is_some() {
+                        // a drag is a press, but not a click
+                        self.finish_drag();
+                        if active.valid() {
+                            self.notify_item(active, BUTTON0_UP);
+                        }
+                    } else if active.valid() {
                         self.notify_item(active, BUTTON0_UP);
                         if active == hot {
                             self.notify_item(active, BUTTON0_HOT_UP);
@@ -657,24 +1937,475 @@ impl<Wgt> Context<Wgt> {
                     }
                     self.active_item = Item::none();
                     self.capture = IDLE;
+                    self.drag_declined = false;
                 } else {
-                    if active.valid() {
-                        self.notify_item(active, BUTTON0_CAPTURE);
+                    if self.drag.is_none() && active.valid() {
+                        self.maybe_start_drag(active);
                     }
-                    if hot == active {
-                        self.hot_item = hot;
-                    }
-                    else {
+                    if self.drag.is_some() {
+                        self.update_drag(hot);
                         self.hot_item = Item::none();
+                    } else {
+                        if active.valid() {
+                            self.notify_item(active, BUTTON0_CAPTURE);
+                        }
+                        if hot == active {
+                            self.hot_item = hot;
+                        }
+                        else {
+                            self.hot_item = Item::none();
+                        }
                     }
                 }
             }
         }
+
+        if self.button_pressed(1) {
+            self.deliver_context_menu();
+        }
+
+        self.pan_canvases(hot);
+        self.deliver_wheel(hot);
+        self.fire_timers();
+        self.deliver_gestures();
+        self.deliver_keys();
+        self.update_tooltip(hot);
+
         // self has changed, reset handles to match current state
         self.last_cursor = self.cursor;
+        self.wheel = Vec2::zero();
+        self.last_buttons = self.buttons;
         let active = self.active_item;
         let hot = self.hot_item;
+        if hot != last_hot || active != last_active {
+            self.redraw = true;
+        }
+        if hot != last_hot {
+            // the last hot item may have gone with a clear()
+            if last_hot.valid() && (last_hot.itemid as uint) < self.count() {
+                self.notify_item(last_hot, LEAVE);
+            }
+            if hot.valid() {
+                self.notify_item(hot, ENTER);
+            }
+        }
         self.hot_tag = if hot.valid() {self.get_tag(hot)} else {0};
         self.active_tag = if active.valid() {self.get_tag(active)} else {0};
     }
+
+    // the cursor rests on 'hot' (or nothing): track the tooltip of it or
+    // of its nearest container with one
+    fn update_tooltip(&mut self, hot: Item) {
+        let mut item = hot;
+        while item.valid() && self.get_tooltip(item).is_none() {
+            item = self.parent(item);
+        }
+        let now = self.time;
+        let cursor = self.cursor;
+        let moved = self.cursor != self.last_cursor;
+        let clicked = self.button_pressed(0) || self.button_pressed(1);
+        if self.tooltip.update(now, item, cursor, moved, clicked) {
+            self.redraw = true;
+        }
+    }
+
+    // button 1 went down: open the context menu of the item under the
+    // cursor, or of the nearest container that has one. outside of a
+    // modal, it dismisses the modal, like button 0.
+    fn deliver_context_menu(&mut self) {
+        let cursor = self.cursor;
+        let hot = self.hit_test(cursor.x, cursor.y);
+        let modal = self.get_modal();
+        if hot.invalid() {
+            let dismissable = self.modals.last().map_or(false, |m| m.dismissable);
+            if modal.valid() && dismissable {
+                self.notify_item(modal, DISMISS);
+            }
+            return;
+        }
+        let mut item = hot;
+        while item.valid() {
+            let menu = self.get_context_menu(item);
+            if menu.valid() {
+                self.notify_item(menu, CONTEXT_MENU);
+                return;
+            }
+            // nothing beneath a modal has a say
+            if item == modal { return; }
+            item = self.parent(item);
+        }
+    }
+
+    // the canvas 'item' is in, or is; none beyond the modal, if any
+    fn canvas_at(&mut self, item: Item) -> Item {
+        let modal = self.get_modal();
+        let mut item = item;
+        while item.valid() {
+            if self.get(item).canvas.is_some() { return item; }
+            if item == modal { break; }
+            item = self.parent(item);
+        }
+        Item::none()
+    }
+
+    // while button 2 is held, the canvas it went down on follows the cursor
+    fn pan_canvases(&mut self, hot: Item) {
+        if self.button_pressed(2) {
+            self.panning = self.canvas_at(hot);
+        } else if !self.get_button(2) {
+            self.panning = Item::none();
+        }
+        let canvas = self.panning;
+        if canvas.invalid() || self.cursor == self.last_cursor { return; }
+        // in the canvas's own pixels, which outer canvases may zoom
+        let (cursor, last) = (self.cursor, self.last_cursor);
+        let to = self.to_local(canvas, cursor);
+        let from = self.to_local(canvas, last);
+        self.pan_canvas(canvas, to.x - from.x, to.y - from.y);
+    }
+
+    // the wheel zooms the innermost canvas under the cursor, unless an
+    // item inside it handles WHEEL itself
+    fn deliver_wheel(&mut self, hot: Item) {
+        let wheel = self.wheel;
+        if wheel.x == 0.0 && wheel.y == 0.0 { return; }
+        let modal = self.get_modal();
+        let mut item = hot;
+        while item.valid() {
+            if self.get(item).event_flags.contains(WHEEL) {
+                self.notify_item(item, WHEEL);
+                return;
+            }
+            if self.get(item).canvas.is_some() {
+                let cursor = self.cursor;
+                self.zoom_canvas(item, ZOOM_STEP.powf(wheel.y), cursor);
+                return;
+            }
+            if item == modal { return; }
+            item = self.parent(item);
+        }
+    }
+
+    // send the queued keys and characters to the focused item; without
+    // one, they're dropped
+    fn deliver_keys(&mut self) {
+        let input = mem::replace(&mut self.key_input, Vec::new());
+        for k in input.iter() {
+            let focus = self.focus_item;
+            if focus.invalid() || self.get(focus).frozen { break; }
+            match *k {
+                KeyPress(key, mods, pressed) => {
+                    self.key = Some(key);
+                    self.modifiers = mods;
+                    self.notify_item(focus, if pressed { KEY_DOWN } else { KEY_UP });
+                    self.key = None;
+                }
+                CharInput(c) => {
+                    self.character = Some(c);
+                    self.notify_item(focus, CHAR);
+                    self.character = None;
+                }
+            }
+        }
+    }
+
+    // the item at (x, y); only the topmost modal's subtree, if there is one
+    fn hit_test(&mut self, x: f32, y: f32) -> Item {
+        let modal = self.get_modal();
+        if modal.valid() {
+            // find_item wants coordinates relative to the modal's parent
+            let parent = self.parent(modal);
+            let origin = if parent.valid() { self.get_abs_rect(parent) } else { Rect::zero() };
+            self.find_item(modal, x - origin.x, y - origin.y, origin.x, origin.y)
+        } else {
+            let root = self.root();
+            self.find_item(root, x, y, 0.0, 0.0)
+        }
+    }
+
+    // send recognized gestures to the innermost item under each gesture's
+    // centroid that handles that kind of gesture
+    fn deliver_gestures(&mut self) {
+        let now = self.time;
+        self.gestures.update(now);
+        let gestures = self.gestures.take_gestures();
+        for g in gestures.iter() {
+            let flag = match g.kind {
+                Tap => TAP,
+                LongPress => LONG_PRESS,
+                Pan => PAN,
+                Pinch => PINCH
+            };
+            let mut item = self.hit_test(g.x, g.y);
+            while item.valid() && !self.get(item).event_flags.contains(flag) {
+                item = self.parent(item);
+            }
+            if item.invalid() { continue; }
+            self.gesture = Some(*g);
+            self.notify_item(item, flag);
+        }
+        self.gesture = None;
+    }
+
+    // ask the pressed item whether it wants to be dragged, once the cursor
+    // has moved far enough
+    fn maybe_start_drag(&mut self, active: Item) {
+        if self.drag_declined { return; }
+        if !self.get(active).event_flags.contains(DRAG_START) { return; }
+        let delta = self.get_cursor_start_delta();
+        let threshold = self.drag_threshold * self.scale;
+        if delta.x*delta.x + delta.y*delta.y < threshold*threshold { return; }
+        self.notify_item(active, DRAG_START);
+        if self.drag.is_none() {
+            // no payload given: not draggable, this time
+            self.drag_declined = true;
+        }
+    }
+
+    // follow the drag to the drop target under the cursor
+    fn update_drag(&mut self, hot: Item) {
+        // the item under the cursor might be part of a drop target
+        let mut target = hot;
+        while target.valid() && !self.get(target).event_flags.contains(DROP) {
+            target = self.parent(target);
+        }
+        let last = self.get_drag_target();
+        if target != last {
+            if last.valid() {
+                self.notify_item(last, DRAG_LEAVE);
+            }
+            match self.drag {
+                Some(ref mut drag) => { drag.target = target; drag.accepted = false; }
+                None => return  // cancelled by a handler
+            }
+            if target.valid() {
+                self.notify_item(target, DRAG_ENTER);
+            }
+            self.redraw = true;
+        }
+        if target.valid() && self.drag.is_some() {
+            self.notify_item(target, DRAG_OVER);
+        }
+    }
+
+    // button 0 is up: drop onto the target if it accepted, then let the
+    // source know how it went
+    fn finish_drag(&mut self) {
+        let target = self.get_drag_target();
+        if target.valid() {
+            if self.is_drag_accepted() {
+                self.notify_item(target, DROP);
+            } else {
+                self.notify_item(target, DRAG_LEAVE);
+            }
+        }
+        let source = self.get_drag_source();
+        if source.valid() {
+            self.notify_item(source, DRAG_END);
+        }
+        self.drag = None;
+        self.redraw = true;
+    }
+
+    // deliver TIMER to the items whose timers are due; one-shots are
+    // dropped, repeating timers rescheduled.
+    fn fire_timers(&mut self) {
+        let now = self.time;
+        let mut fired = Vec::new();
+        for t in self.timers.iter_mut() {
+            if t.due <= now {
+                fired.push((t.id, t.item));
+                if t.is_repeating() { t.reschedule(now); }
+            }
+        }
+        self.timers.retain(|t| t.due > now);
+        if fired.len() > 0 { self.redraw = true; }
+
+        let generation = self.generation;
+        for &(id, item) in fired.iter() {
+            // an earlier handler may have cleared the items away, and the
+            // timers with them; new items may have taken their places
+            if self.generation != generation { break; }
+            self.fired_timer = Some(id);
+            self.notify_item(item, TIMER);
+        }
+        self.fired_timer = None;
+    }
+}
+
+#[test]
+fn test_scale_multiplies_sizes_and_margins() {
+    use oui::TOP;
+
+    let mut ui: Context<()> = Context::create_context();
+    let root = ui.item(());
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 200, 100);
+    let it = ui.item(());
+    ui.set_layout(it, LEFT|TOP);
+    ui.set_size(it, 40, 20);
+    ui.set_margins(it, 4, 8, 0, 0);
+    ui.append(root, it);
+
+    ui.set_scale(1.25);
+    ui.layout();
+    assert_eq!(ui.get_rect(root), Rect { x: 0.0, y: 0.0, w: 250.0, h: 125.0 });
+    assert_eq!(ui.get_rect(it), Rect { x: 5.0, y: 10.0, w: 50.0, h: 25.0 });
+
+    ui.set_scale(1.5);
+    ui.layout();
+    assert_eq!(ui.get_rect(root), Rect { x: 0.0, y: 0.0, w: 300.0, h: 150.0 });
+    assert_eq!(ui.get_rect(it), Rect { x: 6.0, y: 12.0, w: 60.0, h: 30.0 });
+}
+
+#[test]
+fn test_snap_to_whole_pixels_inside_a_fractional_parent() {
+    use oui::TOP;
+
+    let mut ui: Context<()> = Context::create_context();
+    let root = ui.item(());
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 100, 100);
+    // centered, and 49.5 wide at 1.5x: it starts at 50.25
+    let parent = ui.item(());
+    ui.set_size(parent, 33, 33);
+    ui.append(root, parent);
+    let it = ui.item(());
+    ui.set_layout(it, LEFT|TOP);
+    ui.set_size(it, 11, 11);
+    ui.set_margins(it, 1, 1, 0, 0);
+    ui.append(parent, it);
+    ui.set_scale(1.5);
+
+    ui.set_pixel_snap(false);
+    ui.layout();
+    assert_eq!(ui.get_rect(parent).x, 50.25);
+    let r = ui.get_abs_rect(it);
+    assert_eq!((r.x, r.x + r.w), (51.75, 68.25));
+
+    ui.set_pixel_snap(true);
+    ui.layout();
+    let r = ui.get_abs_rect(it);
+    assert_eq!((r.x, r.x + r.w), (52.0, 68.0));
+    assert_eq!((r.y, r.y + r.h), (52.0, 68.0));
+}
+
+#[test]
+fn test_rel_size_is_a_share_of_the_parent() {
+    use oui::{TOP, FILL};
+
+    let mut ui: Context<()> = Context::create_context();
+    let root = ui.item(());
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 300, 200);
+    // 200 x 200, after its margins
+    let parent = ui.item(());
+    ui.set_layout(parent, FILL);
+    ui.set_margins(parent, 50, 0, 50, 0);
+    ui.append(root, parent);
+    let it = ui.item(());
+    ui.set_layout(it, LEFT|TOP);
+    ui.set_rel_size(it, 0.5, 0.5);
+    ui.set_margins(it, 10, 0, 10, 0);
+    ui.append(parent, it);
+    ui.layout();
+
+    // half the parent's width, less its own margins
+    assert_eq!(ui.get_rect(parent).w, 200.0);
+    assert_eq!(ui.get_rect(it), Rect { x: 10.0, y: 0.0, w: 80.0, h: 100.0 });
+}
+
+#[test]
+fn test_aspect_ratio_inside_a_fill() {
+    use oui::{TOP, FILL};
+
+    let mut ui: Context<()> = Context::create_context();
+    let root = ui.item(());
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 300, 200);
+    // the width fills, the height follows it rather than filling too
+    let filled = ui.item(());
+    ui.set_layout(filled, FILL);
+    ui.set_aspect_ratio(filled, 2.0);
+    ui.append(root, filled);
+    // with only a height, the width follows that
+    let fixed = ui.item(());
+    ui.set_layout(fixed, LEFT|TOP);
+    ui.set_size(fixed, 0, 40);
+    ui.set_aspect_ratio(fixed, 2.0);
+    ui.append(root, fixed);
+    ui.layout();
+
+    assert_eq!(ui.get_rect(filled), Rect { x: 0.0, y: 0.0, w: 300.0, h: 150.0 });
+    assert_eq!(ui.get_rect(fixed), Rect { x: 0.0, y: 0.0, w: 80.0, h: 40.0 });
+}
+
+#[test]
+fn test_anchor_to_an_item_elsewhere() {
+    use oui::{TOP, DOWN};
+
+    let mut ui: Context<()> = Context::create_context();
+    let root = ui.item(());
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 300, 200);
+    // the anchor, at (80, 50) in root coordinates
+    let panel = ui.item(());
+    ui.set_size(panel, 200, 150);
+    ui.set_absolute(panel, 50, 20);
+    ui.append(root, panel);
+    let target = ui.item(());
+    ui.set_layout(target, LEFT|TOP);
+    ui.set_size(target, 80, 40);
+    ui.set_margins(target, 30, 30, 0, 0);
+    ui.append(panel, target);
+    // and the items pinned to it, in another part of the tree
+    let layer = ui.item(());
+    ui.set_size(layer, 280, 180);
+    ui.set_absolute(layer, 10, 10);
+    ui.append(root, layer);
+    let badge = ui.item(());
+    ui.set_size(badge, 10, 10);
+    ui.set_anchor(badge, target, RIGHT|TOP, -2, 2);
+    ui.append(layer, badge);
+    let tip = ui.item(());
+    ui.set_size(tip, 60, 20);
+    ui.set_anchor(tip, target, DOWN, 0, 4);
+    ui.append(layer, tip);
+    ui.layout();
+
+    assert_eq!(ui.get_abs_rect(target), Rect { x: 80.0, y: 50.0, w: 80.0, h: 40.0 });
+    // in the top right corner, nudged in
+    assert_eq!(ui.get_abs_rect(badge), Rect { x: 148.0, y: 52.0, w: 10.0, h: 10.0 });
+    assert_eq!(ui.get_rect(badge).x, 138.0);
+    // bottom edges lined up, centered across
+    assert_eq!(ui.get_abs_rect(tip), Rect { x: 90.0, y: 74.0, w: 60.0, h: 20.0 });
+}
+
+#[test]
+fn test_absolute_item_leaves_its_siblings_alone() {
+    use oui::TOP;
+
+    let mut ui: Context<()> = Context::create_context();
+    let root = ui.item(());
+    ui.set_layout(root, LEFT|TOP);
+    let a = ui.item(());
+    ui.set_layout(a, LEFT|TOP);
+    ui.set_size(a, 50, 20);
+    ui.append(root, a);
+    let floating = ui.item(());
+    ui.set_size(floating, 30, 30);
+    ui.set_absolute(floating, 200, 100);
+    ui.append(root, floating);
+    let b = ui.item(());
+    ui.set_layout(b, LEFT|TOP);
+    ui.set_size(b, 50, 20);
+    ui.append(root, b);
+    ui.set_rel_to_left(b, a);
+    ui.layout();
+
+    // the flow is as if it weren't there, and the root only as big as that
+    assert_eq!(ui.get_rect(a), Rect { x: 0.0, y: 0.0, w: 50.0, h: 20.0 });
+    assert_eq!(ui.get_rect(b), Rect { x: 50.0, y: 0.0, w: 50.0, h: 20.0 });
+    assert_eq!(ui.get_rect(root), Rect { x: 0.0, y: 0.0, w: 100.0, h: 20.0 });
+    assert_eq!(ui.get_rect(floating), Rect { x: 200.0, y: 100.0, w: 30.0, h: 30.0 });
 }
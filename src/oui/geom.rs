@@ -1,18 +1,18 @@
 use std::mem::transmute;
 
-#[deriving(Eq,PartialEq, Show)]
+#[deriving(PartialEq, Show)]
 #[repr(C)]
 pub struct Vec2 {
-    pub x: i32,
-    pub y: i32,
+    pub x: f32,
+    pub y: f32,
 }
 impl Vec2 {
-    pub fn zero() -> Vec2 { Vec2 { x: 0, y: 0 } }
-    pub fn as_mut_slice(&mut self) -> &mut [i32, ..2u] { unsafe { transmute(self) } }
+    pub fn zero() -> Vec2 { Vec2 { x: 0.0, y: 0.0 } }
+    pub fn as_mut_slice(&mut self) -> &mut [f32, ..2u] { unsafe { transmute(self) } }
 }
 
-impl<'a> Index<uint, i32> for Vec2 {
-    fn index<'a>(&'a self, index: &uint) -> &'a i32 {
+impl<'a> Index<uint, f32> for Vec2 {
+    fn index<'a>(&'a self, index: &uint) -> &'a f32 {
         match *index {
             0u => { &self.x },
             1u => { &self.y },
@@ -20,8 +20,8 @@ impl<'a> Index<uint, i32> for Vec2 {
         }
     }
 }
-impl<'a> IndexMut<uint, i32> for Vec2 {
-    fn index_mut<'a>(&'a mut self, index: &uint) -> &'a mut i32 {
+impl<'a> IndexMut<uint, f32> for Vec2 {
+    fn index_mut<'a>(&'a mut self, index: &uint) -> &'a mut f32 {
         match *index {
             0u => { &mut self.x },
             1u => { &mut self.y },
@@ -32,21 +32,32 @@ impl<'a> IndexMut<uint, i32> for Vec2 {
 
 
 #[deprecated] //"use geom::Point instead of draw::geom::Point"
-#[deriving(Eq,PartialEq, Show)]
+#[deriving(PartialEq, Show)]
 #[repr(C)]
 pub struct Rect {
-    pub x: i32,
-    pub y: i32,
-    pub w: i32,
-    pub h: i32,
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
 }
 impl Rect {
-    pub fn zero() -> Rect { Rect { x:0, y:0, w:0, h:0 } }
-    pub fn as_mut_slice(&mut self) -> &mut [i32, ..4u] { unsafe { transmute(self) } }
+    pub fn zero() -> Rect { Rect { x:0.0, y:0.0, w:0.0, h:0.0 } }
+    pub fn as_mut_slice(&mut self) -> &mut [f32, ..4u] { unsafe { transmute(self) } }
+
+    /// round the origin to whole pixels, and size so that the far edges
+    /// land on whole pixels too; if the parent's origin is snapped, so is this.
+    pub fn snap(&mut self) {
+        let x = self.x.round();
+        let y = self.y.round();
+        self.w = (self.x + self.w).round() - x;
+        self.h = (self.y + self.h).round() - y;
+        self.x = x;
+        self.y = y;
+    }
 }
 
-impl<'a> Index<uint, i32> for Rect {
-    fn index<'a>(&'a self, index: &uint) -> &'a i32 {
+impl<'a> Index<uint, f32> for Rect {
+    fn index<'a>(&'a self, index: &uint) -> &'a f32 {
         match *index {
             0u => { &self.x },
             1u => { &self.y },
@@ -56,8 +67,8 @@ impl<'a> Index<uint, i32> for Rect {
         }
     }
 }
-impl<'a> IndexMut<uint, i32> for Rect {
-    fn index_mut<'a>(&'a mut self, index: &uint) -> &'a mut i32 {
+impl<'a> IndexMut<uint, f32> for Rect {
+    fn index_mut<'a>(&'a mut self, index: &uint) -> &'a mut f32 {
         match *index {
             0u => { &mut self.x },
             1u => { &mut self.y },
@@ -10,6 +10,7 @@ use super::geom::{
     Vec2,
     Rect
 };
+use super::canvas::CanvasView;
 
 
 #[deriving(Eq, PartialEq, Clone, Show)]
@@ -54,15 +55,45 @@ pub struct ItemImp<Wgt> {
 
     // one or multiple of UIlayoutFlags
     pub layout_flags: LayoutFlags,
-    // size
+    // size (unscaled)
     pub size: Vec2,
+    // the size the item's content asks for (unscaled), where it has no
+    // size of its own and its children don't need more; 0 if none
+    pub preferred: Vec2,
+    // size as a fraction of the parent's size; 0 if unused
+    pub rel_size: Vec2,
+    // width:height ratio to maintain; 0 if unconstrained
+    pub aspect: f32,
     // visited flags for layouting
     pub visited: i32,
     // margin offsets, interpretation depends on flags
-    pub margins: [i32, ..4],
+    // (unscaled; the context's scale factor is applied during layout)
+    pub margins: [f32, ..4],
     // neighbors to position borders to
     pub relto: [Item, ..4],
 
+    // positioned outside of the parent's flow layout
+    pub absolute: bool,
+    // if valid, the item an absolute item is pinned to
+    pub anchor: Item,
+    // edges of the anchor to align to
+    pub anchor_flags: LayoutFlags,
+    // position in parent (or offset from anchor), unscaled
+    pub abs_pos: Vec2,
+
+    // how far the children are scrolled, as a fraction (0..1) of how much
+    // they overflow the item
+    pub scroll: Vec2,
+    // size of the children's bounds at the last layout, before scrolling
+    pub extent: Vec2,
+
+    // if set, the item is a canvas: its children are laid out in canvas
+    // points, and shown and hit through this view
+    pub canvas: Option<CanvasView>,
+
+    // seconds to ease from the old rect to a new one after layout; 0 for none
+    pub transition: f32,
+
     // computed size
     pub computed_size: Vec2,
     // relative rect
@@ -86,6 +117,8 @@ impl<Wgt> ItemImp<Wgt> {
         item.lastkid = Item::none();
         item.nextitem = Item::none();
         item.previtem = Item::none();
+        item.anchor = Item::none();
+        item.canvas = None;
 
         item.widget = wgt;
 
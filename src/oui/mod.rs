@@ -1,10 +1,31 @@
 
 pub use self::item::Item;
 pub use self::context::Context;
+pub use self::timer::TimerId;
+pub use self::scroll::KineticScroll;
+pub use self::tooltip::Tooltip;
+pub use self::canvas::CanvasView;
+pub use self::undo::{Command, SetCell};
+pub use self::property::{Property, Observer, SubscriptionId, SetProperty};
+pub use self::touch::{
+    TouchPhase,
+        Began, Moved, Ended, Cancelled,
+    Gesture,
+    GestureKind,
+        Tap, LongPress, Pan, Pinch,
+};
 
 pub mod geom;
 pub mod item;
 pub mod context;
+pub mod transition;
+pub mod timer;
+pub mod touch;
+pub mod scroll;
+pub mod tooltip;
+pub mod canvas;
+pub mod undo;
+pub mod property;
 
 
 // an OUI context holds a nested hierarchy of Items.
@@ -65,10 +86,94 @@ bitflags!(
         // item has received a new child
         // this can be used to allow container items to configure child items
         // as they appear.
-        static APPEND           = 16
+        static APPEND           = 16,
+        // a timer set on the item with set_timer() or set_interval() is due;
+        // get_fired_timer() tells which one.
+        static TIMER            = 32,
+        // sent to a modal root (see push_modal) when the user clicks
+        // outside of it; the usual response is to pop_modal().
+        static DISMISS          = 64,
+        // the cursor has been dragged past the drag threshold while button 0
+        // is held on the item; call start_drag() with a payload to begin
+        // dragging, or don't, and the press continues as usual.
+        static DRAG_START       = 128,
+        // sent to the drag source when its drag is over, dropped or not;
+        // get_drag_target() and is_drag_accepted() tell the outcome.
+        static DRAG_END         = 256,
+        // a drag has entered a drop target; call accept_drag() if the
+        // payload would be welcome.
+        static DRAG_ENTER       = 512,
+        // a drag is hovering over a drop target (every frame)
+        static DRAG_OVER        = 1024,
+        // a drag has left the drop target, or ended over it without being
+        // accepted
+        static DRAG_LEAVE       = 2048,
+        // an accepted drag was released over the drop target
+        static DROP             = 4096,
+        // all of the events a drop target handles
+        static DROP_TARGET      = 7680,
+        // touch gestures, sent to the innermost item under the gesture's
+        // centroid that handles them; get_gesture() has the details.
+        static TAP              = 8192,
+        static LONG_PRESS       = 16384,
+        static PAN              = 32768,
+        static PINCH            = 65536,
+        // sent to the focused item for each key given to set_key();
+        // get_key() and get_modifiers() tell which.
+        static KEY_DOWN         = 131072,
+        static KEY_UP           = 262144,
+        // sent to the focused item for each character given to set_char();
+        // get_char() tells which.
+        static CHAR             = 524288,
+        // the item has gained the keyboard focus
+        static FOCUS            = 1048576,
+        // the item has lost the keyboard focus
+        static BLUR             = 2097152,
+        // the cursor has moved onto the item (it has become hot)
+        static ENTER            = 4194304,
+        // the cursor has moved off the item
+        static LEAVE            = 8388608,
+        // sent to the menu given to set_context_menu() when button 1 goes
+        // down over its item; get_cursor() tells where
+        static CONTEXT_MENU     = 16777216,
+        // the wheel has turned over the item; get_wheel() tells how far.
+        // sent to the innermost item under the cursor that handles it,
+        // unless a canvas (see set_canvas) is nearer, which zooms instead.
+        static WHEEL            = 33554432
     }
 )
 
+bitflags!(
+    #[deriving(Show)]
+    flags KeyMods: u32 {
+        static MOD_SHIFT        = 1,
+        static MOD_CTRL         = 2,
+        static MOD_ALT          = 4,
+        static MOD_SUPER        = 8
+    }
+)
+
+// keys as the context knows them; the host translates its key codes.
+// the keys widgets act on by themselves have names, the rest go by the
+// host's code.
+#[deriving(Eq, PartialEq, Clone, Show)]
+pub enum Key {
+    KEY_ARROW_LEFT,
+    KEY_ARROW_RIGHT,
+    KEY_ARROW_UP,
+    KEY_ARROW_DOWN,
+    KEY_HOME,
+    KEY_END,
+    KEY_PAGE_UP,
+    KEY_PAGE_DOWN,
+    KEY_BACKSPACE,
+    KEY_DELETE,
+    KEY_ENTER,
+    KEY_ESCAPE,
+    KEY_TAB,
+    KEY_CODE(u32),
+}
+
 
 //pub type Handler = Option<extern "C" fn(arg1: i32, arg2: EventFlags)>;
 pub type Handler<Wgt> = Option<fn(ui: &mut Context<Wgt>, it: Item, evt: EventFlags)>;
@@ -0,0 +1,296 @@
+use std::fmt;
+use std::mem;
+use std::rc::{Rc, Weak};
+use std::cell::{Cell, RefCell};
+
+use oui::undo::Command;
+
+/// told about every change of a property it's subscribed to
+pub trait Observer<T> {
+    fn changed(&self, value: &T);
+    /// an observer that's no longer alive is dropped by the property
+    fn is_alive(&self) -> bool { true }
+}
+
+/// handle to a subscription, for unsubscribing
+#[deriving(Eq, PartialEq, Clone, Show)]
+pub struct SubscriptionId(pub uint);
+
+/// decides what a property may be set to: the value to store (possibly
+/// corrected, e.g. clamped), or None to reject it
+pub type Validator<T> = fn(T) -> Option<T>;
+
+struct PropertyImp<T> {
+    value: RefCell<T>,
+    validator: Cell<Option<Validator<T>>>,
+    observers: RefCell<Vec<(SubscriptionId, Box<Observer<T>+'static>)>>,
+    next_id: Cell<uint>,
+    // how deep in notify() we are; while there, the observers being
+    // called are out of the list, and unsubscribing them is recorded in
+    // 'removed' until they're back
+    notifying: Cell<uint>,
+    removed: RefCell<Vec<SubscriptionId>>,
+}
+
+/// a shared value that tells its observers when it changes. clones share
+/// the value, like Rc<RefCell<T>>.
+pub struct Property<T> {
+    imp: Rc<PropertyImp<T>>,
+}
+
+impl<T: Clone+PartialEq+'static> Property<T> {
+    pub fn new(value: T) -> Property<T> {
+        Property { imp: Rc::new(PropertyImp {
+            value: RefCell::new(value),
+            validator: Cell::new(None),
+            observers: RefCell::new(Vec::new()),
+            next_id: Cell::new(0),
+            notifying: Cell::new(0),
+            removed: RefCell::new(Vec::new()),
+        })}
+    }
+
+    pub fn get(&self) -> T {
+        self.imp.value.borrow().clone()
+    }
+
+    /// store 'value', if the validator accepts it, and notify the
+    /// observers if that's a change. returns whether it changed.
+    pub fn set(&self, value: T) -> bool {
+        let value = match self.imp.validator.get() {
+            Some(validate) => match validate(value) {
+                Some(v) => v,
+                None => return false
+            },
+            None => value
+        };
+        if value == *self.imp.value.borrow() { return false; }
+        *self.imp.value.borrow_mut() = value;
+        self.notify();
+        true
+    }
+
+    /// check values before they're stored; the current value is checked
+    /// (and corrected) too
+    pub fn set_validator(&self, validator: Validator<T>) {
+        self.imp.validator.set(Some(validator));
+        match validator(self.get()) {
+            Some(v) => { self.set(v); }
+            None => {}
+        }
+    }
+
+    pub fn subscribe(&self, observer: Box<Observer<T>+'static>) -> SubscriptionId {
+        let id = SubscriptionId(self.imp.next_id.get());
+        self.imp.next_id.set(self.imp.next_id.get() + 1);
+        let mut observers = self.imp.observers.borrow_mut();
+        observers.retain(|&(_, ref o)| o.is_alive());
+        observers.push((id, observer));
+        id
+    }
+
+    pub fn unsubscribe(&self, id: SubscriptionId) {
+        self.imp.observers.borrow_mut().retain(|&(i, _)| i != id);
+        if self.imp.notifying.get() > 0 {
+            self.imp.removed.borrow_mut().push(id);
+        }
+    }
+
+    /// keep this and 'other' equal: 'other' takes this one's value now,
+    /// and a change to either is made to the other
+    pub fn bind(&self, other: &Property<T>) {
+        other.set(self.get());
+        self.subscribe(box Binding { target: other.imp.downgrade() });
+        other.subscribe(box Binding { target: self.imp.downgrade() });
+    }
+
+    fn notify(&self) {
+        // observers may set properties, this one included, so the list is
+        // taken out while they're called
+        let mut observers = mem::replace(&mut *self.imp.observers.borrow_mut(), Vec::new());
+        observers.retain(|&(_, ref o)| o.is_alive());
+        let value = self.get();
+        self.imp.notifying.set(self.imp.notifying.get() + 1);
+        for &(_, ref o) in observers.iter() {
+            o.changed(&value);
+        }
+        self.imp.notifying.set(self.imp.notifying.get() - 1);
+        // put them back, ahead of any subscribed meanwhile, less any
+        // unsubscribed meanwhile
+        let mut current = self.imp.observers.borrow_mut();
+        let added = mem::replace(&mut *current, observers);
+        current.extend(added.into_iter());
+        let mut removed = self.imp.removed.borrow_mut();
+        current.retain(|&(i, _)| !removed.contains(&i));
+        if self.imp.notifying.get() == 0 {
+            removed.clear();
+        }
+    }
+}
+
+impl<T> Clone for Property<T> {
+    fn clone(&self) -> Property<T> {
+        Property { imp: self.imp.clone() }
+    }
+}
+
+impl<T: fmt::Show> fmt::Show for Property<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Property({})", *self.imp.value.borrow())
+    }
+}
+
+// one direction of a two-way binding; weak, so bound properties can
+// still be dropped
+struct Binding<T> {
+    target: Weak<PropertyImp<T>>,
+}
+
+impl<T: Clone+PartialEq+'static> Observer<T> for Binding<T> {
+    fn changed(&self, value: &T) {
+        match self.target.upgrade() {
+            Some(imp) => { Property { imp: imp }.set(value.clone()); }
+            None => {}
+        }
+    }
+    fn is_alive(&self) -> bool {
+        self.target.upgrade().is_some()
+    }
+}
+
+/// raises a shared flag on any change; how a Context learns that it has
+/// to redraw or relayout. it dies with the flag.
+pub struct ChangeFlag {
+    pub flag: Weak<Cell<bool>>,
+}
+
+impl<T> Observer<T> for ChangeFlag {
+    fn changed(&self, _value: &T) {
+        match self.flag.upgrade() {
+            Some(flag) => flag.set(true),
+            None => {}
+        }
+    }
+    fn is_alive(&self) -> bool {
+        self.flag.upgrade().is_some()
+    }
+}
+
+/// a property that went from 'before' to 'after'; what the blendish
+/// widgets record for undo
+pub struct SetProperty<T> {
+    pub prop: Property<T>,
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: Clone+PartialEq+'static> SetProperty<T> {
+    pub fn new(prop: Property<T>, before: T, after: T) -> SetProperty<T> {
+        SetProperty { prop: prop, before: before, after: after }
+    }
+}
+
+impl<T: Clone+PartialEq+'static> Command for SetProperty<T> {
+    fn undo(&mut self) { self.prop.set(self.before.clone()); }
+    fn redo(&mut self) { self.prop.set(self.after.clone()); }
+}
+
+#[cfg(test)]
+struct Counter {
+    count: Rc<Cell<uint>>,
+}
+
+#[cfg(test)]
+impl Observer<i32> for Counter {
+    fn changed(&self, _value: &i32) {
+        self.count.set(self.count.get() + 1);
+    }
+}
+
+// counts once, then unsubscribes itself
+#[cfg(test)]
+struct Once {
+    prop: Property<i32>,
+    id: SubscriptionId,
+    count: Rc<Cell<uint>>,
+}
+
+#[cfg(test)]
+impl Observer<i32> for Once {
+    fn changed(&self, _value: &i32) {
+        self.count.set(self.count.get() + 1);
+        self.prop.unsubscribe(self.id.clone());
+    }
+}
+
+#[cfg(test)]
+fn percent(v: i32) -> Option<i32> {
+    if v > 100 { None } else if v < 0 { Some(0) } else { Some(v) }
+}
+
+#[test]
+fn test_validator_corrects_and_rejects() {
+    let prop = Property::new(-5i32);
+    prop.set_validator(percent);
+    assert_eq!(prop.get(), 0);
+    assert!(prop.set(50));
+    assert!(!prop.set(200));
+    assert_eq!(prop.get(), 50);
+    assert!(prop.set(-3));
+    assert_eq!(prop.get(), 0);
+}
+
+#[test]
+fn test_subscribe_and_unsubscribe() {
+    let prop = Property::new(0i32);
+    let count = Rc::new(Cell::new(0u));
+    let id = prop.subscribe(box Counter { count: count.clone() });
+    prop.set(1);
+    // no change, no news
+    prop.set(1);
+    assert_eq!(count.get(), 1);
+    prop.unsubscribe(id);
+    prop.set(2);
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn test_dead_observers_are_dropped() {
+    let prop = Property::new(0i32);
+    let flag = Rc::new(Cell::new(false));
+    prop.subscribe(box ChangeFlag { flag: flag.downgrade() });
+    prop.set(1);
+    assert!(flag.get());
+    drop(flag);
+    prop.set(2);
+    assert_eq!(prop.imp.observers.borrow().len(), 0);
+}
+
+#[test]
+fn test_unsubscribe_while_notified() {
+    let prop = Property::new(0i32);
+    let count = Rc::new(Cell::new(0u));
+    let id = SubscriptionId(prop.imp.next_id.get());
+    let once = Once { prop: prop.clone(), id: id.clone(), count: count.clone() };
+    assert_eq!(prop.subscribe(box once), id);
+    prop.set(1);
+    prop.set(2);
+    assert_eq!(count.get(), 1);
+    assert_eq!(prop.imp.observers.borrow().len(), 0);
+}
+
+#[test]
+fn test_bind_both_ways() {
+    let a = Property::new(1i32);
+    let b = Property::new(2i32);
+    a.bind(&b);
+    assert_eq!(b.get(), 1);
+    a.set(5);
+    assert_eq!(b.get(), 5);
+    b.set(7);
+    assert_eq!(a.get(), 7);
+    // the binding doesn't keep b alive
+    drop(b);
+    a.set(9);
+    assert_eq!(a.imp.observers.borrow().len(), 0);
+}
@@ -0,0 +1,310 @@
+use util::{clamp, max, min};
+
+// the simulation runs in fixed steps, so trajectories don't depend on the
+// frame rate
+static STEP: f32 = 1.0 / 120.0;
+// drag samples older than this (seconds) don't count toward fling velocity
+static VELOCITY_WINDOW: f64 = 0.1;
+
+/// drag-to-scroll with inertia. a fling keeps the content moving with
+/// friction; content dragged or flung past either end springs back.
+/// positions are in pixels of content scrolled out of view at the top
+/// (or left); offset() and size() give the 0..1 values that
+/// scroll_handle_rect and draw_scrollbar take.
+pub struct KineticScroll {
+    // fraction of the velocity lost per second is 1 - exp(-friction)
+    pub friction: f32,
+    // below this speed (pixels/second) a fling stops
+    pub min_velocity: f32,
+    // pull of the edge on overscrolled content; the bounce is critically
+    // damped, so it settles without wobbling
+    pub stiffness: f32,
+    // overscroll a drag approaches but never reaches, in pixels
+    pub max_overscroll: f32,
+
+    content: f32,
+    viewport: f32,
+    pos: f32,
+    velocity: f32,
+    // time not yet simulated, less than a STEP
+    leftover: f32,
+
+    dragging: bool,
+    // cursor position when the drag last moved
+    drag_last: f32,
+    // where the drag would have put the content without resistance
+    drag_raw: f32,
+    // (time, pos) during the last VELOCITY_WINDOW of the drag
+    samples: Vec<(f64, f32)>,
+}
+
+impl KineticScroll {
+    pub fn new(content: f32, viewport: f32) -> KineticScroll {
+        KineticScroll {
+            friction: 2.0,
+            min_velocity: 10.0,
+            stiffness: 200.0,
+            max_overscroll: 100.0,
+            content: content,
+            viewport: viewport,
+            pos: 0.0,
+            velocity: 0.0,
+            leftover: 0.0,
+            dragging: false,
+            drag_last: 0.0,
+            drag_raw: 0.0,
+            samples: Vec::new(),
+        }
+    }
+
+    /// change the content and viewport lengths, e.g. after a relayout.
+    /// content that now ends early springs back.
+    pub fn set_extent(&mut self, content: f32, viewport: f32) {
+        self.content = content;
+        self.viewport = viewport;
+    }
+
+    /// the farthest the content scrolls without overscrolling
+    pub fn max_pos(&self) -> f32 {
+        max(0.0, self.content - self.viewport)
+    }
+
+    pub fn get_pos(&self) -> f32 { self.pos }
+
+    pub fn get_velocity(&self) -> f32 { self.velocity }
+
+    /// jump to 'pos', stopping any motion
+    pub fn set_pos(&mut self, pos: f32) {
+        self.pos = clamp(pos, 0.0, self.max_pos());
+        self.velocity = 0.0;
+    }
+
+    /// pixels past the nearest end; negative before the start
+    pub fn overscroll(&self) -> f32 {
+        if self.pos < 0.0 { self.pos }
+        else if self.pos > self.max_pos() { self.pos - self.max_pos() }
+        else { 0.0 }
+    }
+
+    /// scroll handle position, 0..1
+    pub fn offset(&self) -> f32 {
+        let m = self.max_pos();
+        if m <= 0.0 { 0.0 } else { clamp(self.pos / m, 0.0, 1.0) }
+    }
+
+    /// scroll handle length, 0..1; it shrinks while overscrolled, unless
+    /// the content fits, which leaves nothing to scroll
+    pub fn size(&self) -> f32 {
+        if self.content <= self.viewport { return 1.0; }
+        let visible = self.viewport - self.overscroll().abs();
+        clamp(visible / max(self.content, self.viewport), 0.0, 1.0)
+    }
+
+    /// true while a fling or bounce is in progress; the host should keep
+    /// calling step()
+    pub fn is_moving(&self) -> bool {
+        !self.dragging && (self.velocity != 0.0 || self.overscroll() != 0.0)
+    }
+
+    pub fn is_dragging(&self) -> bool { self.dragging }
+
+    /// grab the content with the cursor (or finger) at 'at'; this stops
+    /// any fling
+    pub fn begin_drag(&mut self, now: f64, at: f32) {
+        self.dragging = true;
+        self.velocity = 0.0;
+        self.leftover = 0.0;
+        self.drag_last = at;
+        // undo the resistance, so an overscrolled grab doesn't jump
+        let (over, m) = (self.overscroll(), self.max_overscroll);
+        let r = min(over.abs(), m * 0.99);
+        let raw_over = m * r / (m - r);
+        self.drag_raw = self.pos - over + if over < 0.0 { -raw_over } else { raw_over };
+        self.samples.clear();
+        self.samples.push((now, self.pos));
+    }
+
+    /// the cursor moved to 'at'; the content follows it, with growing
+    /// resistance past the ends
+    pub fn drag_to(&mut self, now: f64, at: f32) {
+        if !self.dragging { return; }
+        self.drag_raw += self.drag_last - at;
+        self.drag_last = at;
+        let m = self.max_overscroll;
+        let rubber = |d: f32| m * d / (d + m);
+        let (raw, end) = (self.drag_raw, self.max_pos());
+        self.pos = if raw < 0.0 { -rubber(-raw) }
+            else if raw > end { end + rubber(raw - end) }
+            else { raw };
+        self.samples.push((now, self.pos));
+        self.samples.retain(|&(t, _)| now - t <= VELOCITY_WINDOW);
+    }
+
+    /// let go of the content; it keeps the velocity of the last
+    /// VELOCITY_WINDOW of the drag, so a cursor that came to rest first
+    /// doesn't fling
+    pub fn end_drag(&mut self, now: f64) {
+        if !self.dragging { return; }
+        self.dragging = false;
+        self.samples.retain(|&(t, _)| now - t <= VELOCITY_WINDOW);
+        self.velocity = match (self.samples.head(), self.samples.last()) {
+            (Some(&(t0, p0)), Some(&(t1, p1))) if now > t0 && t1 > t0 => {
+                (p1 - p0) / (now - t0) as f32
+            }
+            _ => 0.0
+        };
+        self.samples.clear();
+    }
+
+    /// start moving at 'velocity' pixels/second, as if flung
+    pub fn fling(&mut self, velocity: f32) {
+        self.dragging = false;
+        self.velocity = velocity;
+    }
+
+    /// advance the simulation by 'dt' seconds
+    pub fn step(&mut self, dt: f32) {
+        if self.dragging { return; }
+        self.leftover += dt;
+        while self.leftover >= STEP {
+            self.leftover -= STEP;
+            self.substep(STEP);
+            if !self.is_moving() {
+                self.leftover = 0.0;
+                break;
+            }
+        }
+    }
+
+    fn substep(&mut self, h: f32) {
+        let over = self.overscroll();
+        if over == 0.0 {
+            self.velocity *= (-self.friction * h).exp();
+            if self.velocity.abs() < self.min_velocity { self.velocity = 0.0; }
+            self.pos += self.velocity * h;
+            return;
+        }
+        // past an end: a spring toward it
+        let damping = 2.0 * self.stiffness.sqrt();
+        let accel = -self.stiffness * over - damping * self.velocity;
+        self.velocity += accel * h;
+        self.pos += self.velocity * h;
+        // at rest at the end
+        let edge = if over < 0.0 { 0.0 } else { self.max_pos() };
+        if (self.pos - edge).abs() < 0.5 && self.velocity.abs() < self.min_velocity {
+            self.pos = edge;
+            self.velocity = 0.0;
+        }
+    }
+}
+
+// run until the motion stops, at most 'limit' seconds; returns the
+// positions after each frame of 'dt'
+fn simulate(s: &mut KineticScroll, dt: f32, limit: f32) -> Vec<f32> {
+    let mut trail = Vec::new();
+    let mut t = 0.0;
+    while s.is_moving() && t < limit {
+        s.step(dt);
+        trail.push(s.get_pos());
+        t += dt;
+    }
+    trail
+}
+
+#[test]
+fn test_fling_decelerates_and_stops() {
+    let mut s = KineticScroll::new(10000.0, 100.0);
+    s.set_pos(1000.0);
+    s.fling(1000.0);
+    let trail = simulate(&mut s, 1.0/60.0, 10.0);
+    assert!(!s.is_moving());
+    // monotonic, with shrinking steps
+    for i in range(1, trail.len() - 1) {
+        assert!(trail[i] >= trail[i-1]);
+        assert!(trail[i+1] - trail[i] <= trail[i] - trail[i-1] + 1e-3);
+    }
+    // exponential decay travels about v/friction
+    let travelled = s.get_pos() - 1000.0;
+    assert!(travelled > 450.0 && travelled < 500.0);
+}
+
+#[test]
+fn test_trajectory_independent_of_frame_rate() {
+    let mut a = KineticScroll::new(10000.0, 100.0);
+    let mut b = KineticScroll::new(10000.0, 100.0);
+    a.fling(800.0);
+    b.fling(800.0);
+    for _ in range(0u, 30) { a.step(1.0/30.0); }
+    for _ in range(0u, 120) { b.step(1.0/120.0); }
+    assert!((a.get_pos() - b.get_pos()).abs() < 1.0);
+}
+
+#[test]
+fn test_fling_past_end_bounces_back() {
+    let mut s = KineticScroll::new(1000.0, 200.0);
+    s.set_pos(700.0);
+    s.fling(2000.0);
+    let trail = simulate(&mut s, 1.0/60.0, 10.0);
+    let peak = trail.iter().fold(0.0f32, |m, &p| max(m, p));
+    assert!(peak > 800.0);
+    assert!(!s.is_moving());
+    assert_eq!(s.get_pos(), 800.0);
+    assert_eq!(s.offset(), 1.0);
+    // critically damped: it doesn't undershoot the end on the way back
+    let after_peak = trail.iter().skip_while(|&&p| p < peak);
+    for &p in after_peak { assert!(p >= 800.0); }
+}
+
+#[test]
+fn test_drag_follows_cursor_and_flings() {
+    let mut s = KineticScroll::new(1000.0, 100.0);
+    s.begin_drag(0.0, 500.0);
+    s.drag_to(0.05, 450.0);
+    s.drag_to(0.10, 400.0);
+    assert_eq!(s.get_pos(), 100.0);
+    assert!(!s.is_moving());
+    s.end_drag(0.10);
+    // 100 pixels in 0.1 seconds
+    assert!((s.get_velocity() - 1000.0).abs() < 1.0);
+    assert!(s.is_moving());
+}
+
+#[test]
+fn test_drag_that_rests_does_not_fling() {
+    let mut s = KineticScroll::new(1000.0, 100.0);
+    s.begin_drag(0.0, 500.0);
+    s.drag_to(0.05, 400.0);
+    s.end_drag(0.5);
+    assert_eq!(s.get_velocity(), 0.0);
+    assert!(!s.is_moving());
+}
+
+#[test]
+fn test_overscroll_drag_is_resisted_and_springs_back() {
+    let mut s = KineticScroll::new(1000.0, 100.0);
+    s.begin_drag(0.0, 0.0);
+    s.drag_to(0.1, 200.0);
+    // pulled down by 200, moved by less, never past max_overscroll
+    assert!(s.get_pos() < 0.0 && s.get_pos() > -100.0);
+    assert!(s.size() < 0.1);
+    assert_eq!(s.offset(), 0.0);
+    s.end_drag(1.0);
+    simulate(&mut s, 1.0/60.0, 10.0);
+    assert_eq!(s.get_pos(), 0.0);
+}
+
+#[test]
+fn test_offset_and_size() {
+    let mut s = KineticScroll::new(400.0, 100.0);
+    assert_eq!(s.size(), 0.25);
+    s.set_pos(150.0);
+    assert_eq!(s.offset(), 0.5);
+    // content that fits doesn't scroll: the handle fills the track while
+    // the content, left at 150, springs back
+    s.set_extent(50.0, 100.0);
+    assert_eq!(s.get_pos(), 150.0);
+    assert_eq!(s.size(), 1.0);
+    assert_eq!(s.offset(), 0.0);
+    simulate(&mut s, 1.0/60.0, 10.0);
+    assert_eq!(s.get_pos(), 0.0);
+}
@@ -0,0 +1,90 @@
+use oui::item::Item;
+#[cfg(test)]
+use oui::{Context, EventFlags, TIMER};
+
+/// handle to a timer registered with a Context, for cancelling it
+#[deriving(Eq, PartialEq, Clone, Show)]
+pub struct TimerId(pub u32);
+
+pub struct Timer {
+    pub id: TimerId,
+    // item that receives the TIMER event
+    pub item: Item,
+    // time the timer fires next, in seconds
+    pub due: f64,
+    // seconds between repeats; 0 for a one-shot timer
+    pub interval: f64,
+}
+
+impl Timer {
+    pub fn is_repeating(&self) -> bool { self.interval > 0.0 }
+
+    /// move a repeating timer past 'now'. a host that stalled for a while
+    /// gets one event, not a burst of them.
+    pub fn reschedule(&mut self, now: f64) {
+        assert!(self.is_repeating());
+        if self.due > now { return; }
+        self.due += (((now - self.due) / self.interval).floor() + 1.0) * self.interval;
+    }
+}
+
+#[cfg(test)]
+fn rebuild(ui: &mut Context<uint>) {
+    ui.clear();
+    for _ in range(0u, 3) {
+        let it = ui.item(10u);
+        ui.set_handler(it, Some(timerhandler), TIMER);
+    }
+}
+
+// the first item to fire rebuilds the ui; the items built then count
+// the timers they're told about
+#[cfg(test)]
+fn timerhandler(ui: &mut Context<uint>, item: Item, _event: EventFlags) {
+    if *ui.get_widget(item) == 0 {
+        rebuild(ui);
+    } else {
+        *ui.get_widget(item) += 1;
+    }
+}
+
+#[test]
+fn test_cleared_timers_stay_quiet() {
+    let mut ui: Context<uint> = Context::create_context();
+    let root = ui.item(0u);
+    for _ in range(0u, 2) {
+        let it = ui.item(0u);
+        ui.set_handler(it, Some(timerhandler), TIMER);
+        ui.append(root, it);
+        ui.set_timer(it, 0.5);
+    }
+    ui.set_time(1.0);
+    ui.process();
+    // the second timer was the old item 2's, not the new one's
+    assert_eq!(*ui.get_widget(Item::wrap(2)), 10);
+}
+
+#[test]
+fn test_interval_repeats_without_catching_up() {
+    let mut ui: Context<uint> = Context::create_context();
+    let root = ui.item(0u);
+    let it = ui.item(1u);
+    ui.set_handler(it, Some(timerhandler), TIMER);
+    ui.append(root, it);
+    ui.set_interval(it, 0.5);
+    for &t in [0.5f64, 0.6, 1.0].iter() {
+        ui.set_time(t);
+        ui.process();
+    }
+    assert_eq!(*ui.get_widget(it), 3);
+    // stalled for nine seconds: one event, and back on the beat
+    ui.set_time(10.2);
+    ui.process();
+    assert_eq!(*ui.get_widget(it), 4);
+    ui.set_time(10.4);
+    ui.process();
+    assert_eq!(*ui.get_widget(it), 4);
+    ui.set_time(10.5);
+    ui.process();
+    assert_eq!(*ui.get_widget(it), 5);
+}
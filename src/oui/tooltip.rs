@@ -0,0 +1,151 @@
+use oui::item::Item;
+use oui::geom::Vec2;
+
+/// seconds the cursor rests on an item before its tooltip shows
+pub static DEFAULT_DELAY: f64 = 0.5;
+
+/// what an item's tooltip says
+#[deriving(Clone, PartialEq, Show)]
+pub struct Tooltip {
+    pub text: String,
+    // the keys that do the same, e.g. "Ctrl+S"; may be empty
+    pub shortcut: String,
+    // a longer explanation, under the text; may be empty
+    pub description: String,
+}
+
+impl Tooltip {
+    pub fn new(text: &str) -> Tooltip {
+        Tooltip {
+            text: text.to_string(),
+            shortcut: String::new(),
+            description: String::new(),
+        }
+    }
+
+    pub fn shortcut(mut self, shortcut: &str) -> Tooltip {
+        self.shortcut = shortcut.to_string();
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Tooltip {
+        self.description = description.to_string();
+        self
+    }
+}
+
+/// decides when a tooltip shows: once the cursor has rested on an item
+/// with one for 'delay' seconds. moving the cursor hides it and starts
+/// the wait over; a click hides it until the cursor moves again.
+pub struct TooltipTracker {
+    pub delay: f64,
+    // the item with a tooltip under the cursor, and since when it's been
+    // resting there
+    item: Item,
+    since: f64,
+    // a click put tooltips away until the cursor moves
+    suppressed: bool,
+    shown: bool,
+    // the cursor when the tooltip appeared
+    at: Vec2,
+}
+
+impl TooltipTracker {
+    pub fn new(delay: f64) -> TooltipTracker {
+        TooltipTracker {
+            delay: delay,
+            item: Item::none(),
+            since: 0.0,
+            suppressed: false,
+            shown: false,
+            at: Vec2::zero(),
+        }
+    }
+
+    /// a frame at 'now': 'item' (or none) is under the cursor at
+    /// 'cursor', which has moved since the last frame or not, and a
+    /// button went down or not. true if the tooltip appeared or vanished.
+    pub fn update(&mut self, now: f64, item: Item, cursor: Vec2,
+        moved: bool, clicked: bool
+    ) -> bool {
+        let was_shown = self.shown;
+        if clicked {
+            self.suppressed = true;
+            self.shown = false;
+        } else if moved {
+            self.suppressed = false;
+            self.shown = false;
+            self.since = now;
+        }
+        if item != self.item {
+            self.item = item;
+            self.since = now;
+            self.shown = false;
+        }
+        if !self.shown && !self.suppressed && self.item.valid()
+        && now - self.since >= self.delay {
+            self.shown = true;
+            self.at = cursor;
+        }
+        self.shown != was_shown
+    }
+
+    /// the item whose tooltip is showing, or none
+    pub fn shown(&self) -> Item {
+        if self.shown { self.item } else { Item::none() }
+    }
+
+    /// where the cursor was when the tooltip appeared
+    pub fn position(&self) -> Vec2 { self.at }
+
+    /// when a tooltip is due to appear, if one is waiting
+    pub fn next_deadline(&self) -> Option<f64> {
+        if self.shown || self.suppressed || self.item.invalid() { None }
+        else { Some(self.since + self.delay) }
+    }
+
+    pub fn reset(&mut self) {
+        self.item = Item::none();
+        self.suppressed = false;
+        self.shown = false;
+    }
+}
+
+#[test]
+fn test_shows_after_resting_for_the_delay() {
+    let mut tips = TooltipTracker::new(0.5);
+    let at = Vec2 { x: 10.0, y: 20.0 };
+    let item = Item::wrap(3);
+    tips.update(1.0, item, at, true, false);
+    assert_eq!(tips.next_deadline(), Some(1.5));
+    assert!(!tips.update(1.4, item, at, false, false));
+    assert!(tips.shown().invalid());
+    assert!(tips.update(1.5, item, at, false, false));
+    assert_eq!(tips.shown(), item);
+    assert_eq!(tips.position(), at);
+    // moving hides it, and the wait starts over
+    let moved = Vec2 { x: 12.0, y: 20.0 };
+    assert!(tips.update(1.6, item, moved, true, false));
+    assert!(!tips.update(2.0, item, moved, false, false));
+    assert!(tips.update(2.2, item, moved, false, false));
+    assert_eq!(tips.position(), moved);
+}
+
+#[test]
+fn test_click_hides_until_the_cursor_moves() {
+    let mut tips = TooltipTracker::new(0.5);
+    let at = Vec2 { x: 10.0, y: 20.0 };
+    let item = Item::wrap(3);
+    tips.update(0.0, item, at, true, false);
+    tips.update(0.5, item, at, false, false);
+    assert!(tips.update(0.6, item, at, false, true));
+    assert!(!tips.update(5.0, item, at, false, false));
+    assert_eq!(tips.next_deadline(), None);
+    tips.update(5.1, item, at, true, false);
+    tips.update(5.7, item, at, false, false);
+    assert_eq!(tips.shown(), item);
+    // nothing under the cursor, nothing shown
+    tips.update(5.8, Item::none(), at, true, false);
+    tips.update(9.0, Item::none(), at, false, false);
+    assert!(tips.shown().invalid());
+}
@@ -0,0 +1,294 @@
+use std::mem;
+use std::f32::consts::PI;
+
+#[deriving(PartialEq, Show)]
+pub enum TouchPhase {
+    Began,
+    Moved,
+    Ended,
+    // the system took the touch away (e.g. for a system gesture)
+    Cancelled,
+}
+
+#[deriving(PartialEq, Show)]
+pub struct TouchPoint {
+    // identifies a finger for as long as it's down
+    pub id: u64,
+    pub x: f32,
+    pub y: f32,
+    pub phase: TouchPhase,
+}
+
+#[deriving(PartialEq, Show)]
+pub enum GestureKind {
+    Tap,
+    LongPress,
+    Pan,
+    Pinch,
+}
+
+#[deriving(PartialEq, Show)]
+pub struct Gesture {
+    pub kind: GestureKind,
+    // centroid of the touches
+    pub x: f32,
+    pub y: f32,
+    // Pan: movement of the centroid since the previous Pan
+    pub dx: f32,
+    pub dy: f32,
+    // Pinch: finger distance, relative to when the second finger came down
+    pub scale: f32,
+    // Pinch: rotation since the second finger came down, radians clockwise
+    pub rotation: f32,
+}
+
+#[deriving(PartialEq)]
+enum Mode {
+    // no fingers down
+    NO_GESTURE,
+    // one finger down, not (yet) moved much
+    MAYBE_TAP,
+    PANNING,
+    PINCHING,
+    // the gesture has been recognized (or abandoned); wait for all fingers up
+    FINISHED,
+}
+
+struct Touch {
+    id: u64,
+    x: f32,
+    y: f32,
+    start_x: f32,
+    start_y: f32,
+}
+
+/// turns a stream of touch points into taps, long presses, pans and
+/// pinches. it only needs the touches and the time, so it can be driven
+/// by synthetic streams as well as by a touch panel.
+pub struct GestureRecognizer {
+    // longest touch, in seconds, that still counts as a tap
+    pub tap_time: f64,
+    // seconds a finger has to rest to make a long press
+    pub long_press_time: f64,
+    // distance a finger may wander before a tap becomes a pan
+    pub slop: f32,
+
+    touches: Vec<Touch>,
+    mode: Mode,
+    // when the first finger came down
+    start_time: f64,
+    // centroid at the previous Pan
+    last_x: f32,
+    last_y: f32,
+    // finger distance and angle when the pinch began
+    pinch_dist: f32,
+    pinch_angle: f32,
+
+    gestures: Vec<Gesture>,
+}
+
+impl GestureRecognizer {
+    pub fn new() -> GestureRecognizer {
+        GestureRecognizer {
+            tap_time: 0.3,
+            long_press_time: 0.5,
+            slop: 8.0,
+            touches: Vec::new(),
+            mode: NO_GESTURE,
+            start_time: 0.0,
+            last_x: 0.0,
+            last_y: 0.0,
+            pinch_dist: 1.0,
+            pinch_angle: 0.0,
+            gestures: Vec::new(),
+        }
+    }
+
+    /// feed a touch point, at time 'now' (seconds)
+    pub fn touch(&mut self, now: f64, pt: TouchPoint) {
+        match pt.phase {
+            Began => {
+                self.touches.push(Touch {
+                    id: pt.id, x: pt.x, y: pt.y, start_x: pt.x, start_y: pt.y
+                });
+                match self.touches.len() {
+                    1 => {
+                        self.mode = MAYBE_TAP;
+                        self.start_time = now;
+                    }
+                    2 => self.begin_pinch(),
+                    _ => {}
+                }
+            }
+            Moved => {
+                match self.touches.iter_mut().find(|t| t.id == pt.id) {
+                    Some(t) => { t.x = pt.x; t.y = pt.y; }
+                    None => return
+                }
+                self.moved();
+            }
+            Ended | Cancelled => {
+                let n = self.touches.len();
+                self.touches.retain(|t| t.id != pt.id);
+                if self.touches.len() == n { return; }  // never saw it go down
+                match self.mode {
+                    MAYBE_TAP if pt.phase == Ended && now - self.start_time <= self.tap_time => {
+                        self.emit(Tap, pt.x, pt.y, 0.0, 0.0, 1.0, 0.0);
+                    }
+                    _ => {}
+                }
+                self.mode = if self.touches.is_empty() { NO_GESTURE } else { FINISHED };
+            }
+        }
+    }
+
+    /// advance time without new touches; needed to recognize long presses
+    pub fn update(&mut self, now: f64) {
+        if self.mode == MAYBE_TAP && now - self.start_time >= self.long_press_time {
+            let (x, y) = (self.touches[0].x, self.touches[0].y);
+            self.emit(LongPress, x, y, 0.0, 0.0, 1.0, 0.0);
+            self.mode = FINISHED;
+        }
+    }
+
+    /// when update() has to be called next, if a gesture depends on time
+    pub fn next_deadline(&self) -> Option<f64> {
+        if self.mode == MAYBE_TAP { Some(self.start_time + self.long_press_time) }
+        else { None }
+    }
+
+    /// the gestures recognized since the last call
+    pub fn take_gestures(&mut self) -> Vec<Gesture> {
+        mem::replace(&mut self.gestures, Vec::new())
+    }
+
+    fn moved(&mut self) {
+        if self.mode == MAYBE_TAP {
+            let (dx, dy) = {
+                let t = &self.touches[0];
+                (t.x - t.start_x, t.y - t.start_y)
+            };
+            if dx*dx + dy*dy > self.slop*self.slop {
+                // pan deltas count from where the finger came down
+                self.mode = PANNING;
+                self.last_x = self.touches[0].start_x;
+                self.last_y = self.touches[0].start_y;
+            }
+        }
+        let (x, y) = self.centroid();
+        match self.mode {
+            PANNING => {
+                let (dx, dy) = (x - self.last_x, y - self.last_y);
+                self.last_x = x;
+                self.last_y = y;
+                self.emit(Pan, x, y, dx, dy, 1.0, 0.0);
+            }
+            PINCHING => {
+                let (dist, angle) = self.span();
+                let scale = dist / self.pinch_dist;
+                let rotation = wrap_angle(angle - self.pinch_angle);
+                self.emit(Pinch, x, y, 0.0, 0.0, scale, rotation);
+            }
+            _ => {}
+        }
+    }
+
+    fn begin_pinch(&mut self) {
+        let (dist, angle) = self.span();
+        self.mode = PINCHING;
+        self.pinch_dist = if dist > 0.0 { dist } else { 1.0 };
+        self.pinch_angle = angle;
+    }
+
+    fn centroid(&self) -> (f32, f32) {
+        let n = self.touches.len();
+        if n == 0 { return (0.0, 0.0); }
+        let (sx, sy) = self.touches.iter().fold((0.0, 0.0), |(sx, sy), t| (sx + t.x, sy + t.y));
+        (sx / n as f32, sy / n as f32)
+    }
+
+    // distance and angle between the first two fingers
+    fn span(&self) -> (f32, f32) {
+        let dx = self.touches[1].x - self.touches[0].x;
+        let dy = self.touches[1].y - self.touches[0].y;
+        ((dx*dx + dy*dy).sqrt(), dy.atan2(dx))
+    }
+
+    fn emit(&mut self, kind: GestureKind, x: f32, y: f32, dx: f32, dy: f32,
+        scale: f32, rotation: f32
+    ) {
+        self.gestures.push(Gesture {
+            kind: kind, x: x, y: y, dx: dx, dy: dy, scale: scale, rotation: rotation
+        });
+    }
+}
+
+// into -PI..PI, so a turn across the x axis doesn't jump by a full circle
+fn wrap_angle(a: f32) -> f32 {
+    let mut a = a;
+    while a > PI { a -= 2.0*PI; }
+    while a <= -PI { a += 2.0*PI; }
+    a
+}
+
+
+fn pt(id: u64, x: f32, y: f32, phase: TouchPhase) -> TouchPoint {
+    TouchPoint { id: id, x: x, y: y, phase: phase }
+}
+
+#[test]
+fn test_quick_touch_is_a_tap() {
+    let mut gr = GestureRecognizer::new();
+    gr.touch(0.0, pt(1, 10.0, 20.0, Began));
+    gr.touch(0.1, pt(1, 11.0, 20.0, Moved));
+    gr.touch(0.2, pt(1, 11.0, 20.0, Ended));
+    let gs = gr.take_gestures();
+    assert_eq!(gs.len(), 1);
+    assert_eq!(gs[0].kind, Tap);
+    assert_eq!((gs[0].x, gs[0].y), (11.0, 20.0));
+}
+
+#[test]
+fn test_resting_touch_is_a_long_press_not_a_tap() {
+    let mut gr = GestureRecognizer::new();
+    gr.touch(0.0, pt(1, 5.0, 5.0, Began));
+    gr.update(0.4);
+    assert!(gr.take_gestures().is_empty());
+    assert_eq!(gr.next_deadline(), Some(0.5));
+    gr.update(0.6);
+    let gs = gr.take_gestures();
+    assert_eq!(gs.len(), 1);
+    assert_eq!(gs[0].kind, LongPress);
+    gr.touch(0.7, pt(1, 5.0, 5.0, Ended));
+    assert!(gr.take_gestures().is_empty());
+}
+
+#[test]
+fn test_moving_touch_pans_by_deltas() {
+    let mut gr = GestureRecognizer::new();
+    gr.touch(0.0, pt(1, 0.0, 0.0, Began));
+    gr.touch(0.1, pt(1, 5.0, 0.0, Moved));   // within slop
+    assert!(gr.take_gestures().is_empty());
+    gr.touch(0.2, pt(1, 20.0, 0.0, Moved));
+    gr.touch(0.3, pt(1, 25.0, 3.0, Moved));
+    gr.touch(0.4, pt(1, 25.0, 3.0, Ended));
+    let gs = gr.take_gestures();
+    assert_eq!(gs.len(), 2);
+    assert_eq!(gs[0].kind, Pan);
+    assert_eq!((gs[0].dx, gs[0].dy), (20.0, 0.0));
+    assert_eq!((gs[1].dx, gs[1].dy), (5.0, 3.0));
+}
+
+#[test]
+fn test_two_fingers_pinch_and_rotate() {
+    let mut gr = GestureRecognizer::new();
+    gr.touch(0.0, pt(1, 0.0, 0.0, Began));
+    gr.touch(0.0, pt(2, 10.0, 0.0, Began));
+    gr.touch(0.1, pt(2, 0.0, 20.0, Moved));
+    let gs = gr.take_gestures();
+    assert_eq!(gs.len(), 1);
+    assert_eq!(gs[0].kind, Pinch);
+    assert_eq!((gs[0].x, gs[0].y), (0.0, 10.0));
+    assert!((gs[0].scale - 2.0).abs() < 1e-5);
+    assert!((gs[0].rotation - PI/2.0).abs() < 1e-5);
+}
@@ -0,0 +1,119 @@
+use util::clamp;
+use oui::geom::Rect;
+
+/// an item's on-screen rect, easing from where it was drawn toward
+/// where layout() has placed it now. rects are in root coordinates.
+pub struct Transition {
+    pub from: Rect,
+    pub to: Rect,
+    // time the transition started, in seconds
+    pub start: f64,
+    // length of the transition, in seconds
+    pub duration: f32,
+}
+
+impl Transition {
+    /// a transition that's already at rest at 'rect'
+    pub fn at(rect: Rect) -> Transition {
+        Transition { from: rect, to: rect, start: 0.0, duration: 0.0 }
+    }
+
+    /// linear progress in 0..1 at time 'now'
+    pub fn progress(&self, now: f64) -> f32 {
+        if self.duration <= 0.0 { return 1.0; }
+        let t = (now - self.start) / self.duration as f64;
+        clamp(t as f32, 0.0, 1.0)
+    }
+
+    pub fn is_running(&self, now: f64) -> bool {
+        self.progress(now) < 1.0
+    }
+
+    /// the eased rect at time 'now'
+    pub fn rect_at(&self, now: f64) -> Rect {
+        let t = ease(self.progress(now));
+        Rect {
+            x: lerp(self.from.x, self.to.x, t),
+            y: lerp(self.from.y, self.to.y, t),
+            w: lerp(self.from.w, self.to.w, t),
+            h: lerp(self.from.h, self.to.h, t),
+        }
+    }
+}
+
+/// ease in and out (smoothstep)
+pub fn ease(t: f32) -> f32 {
+    t*t*(3.0 - 2.0*t)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a)*t
+}
+
+#[test]
+fn test_transitions_need_a_unique_tag() {
+    use oui::{Context, Item, LEFT, TOP};
+
+    let mut ui: Context<()> = Context::create_context();
+    let root = ui.item(());
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 200, 100);
+    // two sharing tag 1, one without a tag, and one tagged 2
+    let mut items: Vec<Item> = Vec::new();
+    for &tag in [1u64, 1, 0, 2].iter() {
+        let it = ui.item(());
+        ui.set_tag(it, tag);
+        ui.set_size(it, 10, 10);
+        ui.set_absolute(it, 0, 0);
+        ui.set_transition(it, 1.0);
+        ui.append(root, it);
+        items.push(it);
+    }
+    ui.layout();
+    for &it in items.iter() {
+        ui.set_absolute(it, 100, 0);
+    }
+    ui.layout();
+
+    // only the one with a tag of its own eases over
+    assert!(ui.get_display_rect(items[0]).is_none());
+    assert!(ui.get_display_rect(items[1]).is_none());
+    assert!(ui.get_display_rect(items[2]).is_none());
+    assert!(ui.get_display_rect(items[3]).is_some());
+}
+
+#[test]
+fn test_display_rect_eases_to_the_layout() {
+    use oui::{Context, LEFT, TOP};
+
+    let mut ui: Context<()> = Context::create_context();
+    let root = ui.item(());
+    ui.set_layout(root, LEFT|TOP);
+    ui.set_size(root, 200, 100);
+    let it = ui.item(());
+    ui.set_tag(it, 1);
+    ui.set_size(it, 20, 10);
+    ui.set_absolute(it, 0, 0);
+    ui.set_transition(it, 1.0);
+    ui.append(root, it);
+    ui.set_time(1.0);
+    ui.layout();
+    ui.set_absolute(it, 100, 0);
+    ui.layout();
+
+    // a quarter of the way in time, smoothstep has it 15.6% of the way
+    ui.set_time(1.25);
+    assert!(ui.is_animating());
+    assert_eq!(ease(0.25), 0.15625);
+    assert_eq!(ui.get_display_rect(it), Some(Rect { x: 15.625, y: 0.0, w: 20.0, h: 10.0 }));
+    // it's hit where it's going, not where it's drawn
+    assert_eq!(ui.find_item(root, 110.0, 5.0, 0.0, 0.0), it);
+    assert_eq!(ui.find_item(root, 20.0, 5.0, 0.0, 0.0), root);
+    ui.set_time(1.5);
+    assert_eq!(ui.get_display_rect(it), Some(Rect { x: 50.0, y: 0.0, w: 20.0, h: 10.0 }));
+
+    // and at rest once it's over
+    ui.set_time(2.0);
+    assert!(!ui.is_animating());
+    assert_eq!(ui.get_display_rect(it), None);
+}
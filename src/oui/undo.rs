@@ -0,0 +1,93 @@
+use std::rc::Rc;
+use std::cell::Cell;
+
+/// a change that can be taken back and made again. redo() is also how a
+/// command is first applied, by Context::do_command.
+pub trait Command {
+    fn undo(&mut self);
+    fn redo(&mut self);
+}
+
+/// a shared value that went from 'before' to 'after'; see also
+/// property::SetProperty
+pub struct SetCell<T> {
+    pub cell: Rc<Cell<T>>,
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: Copy> SetCell<T> {
+    pub fn new(cell: Rc<Cell<T>>, before: T, after: T) -> SetCell<T> {
+        SetCell { cell: cell, before: before, after: after }
+    }
+}
+
+impl<T: Copy> Command for SetCell<T> {
+    fn undo(&mut self) { self.cell.set(self.before); }
+    fn redo(&mut self) { self.cell.set(self.after); }
+}
+
+/// commands done, and commands undone since
+pub struct UndoStack {
+    done: Vec<Box<Command+'static>>,
+    undone: Vec<Box<Command+'static>>,
+    // most commands kept; the oldest are dropped first
+    limit: uint,
+}
+
+impl UndoStack {
+    pub fn new() -> UndoStack {
+        UndoStack { done: Vec::new(), undone: Vec::new(), limit: 100 }
+    }
+
+    pub fn set_limit(&mut self, limit: uint) {
+        self.limit = limit;
+        self.trim();
+    }
+
+    /// record a change that has already been applied. anything undone
+    /// can't be redone after this.
+    pub fn push(&mut self, cmd: Box<Command+'static>) {
+        self.undone.clear();
+        self.done.push(cmd);
+        self.trim();
+    }
+
+    /// take back the last change; false if there's none
+    pub fn undo(&mut self) -> bool {
+        match self.done.pop() {
+            Some(mut cmd) => {
+                cmd.undo();
+                self.undone.push(cmd);
+                true
+            }
+            None => false
+        }
+    }
+
+    /// make the last undone change again; false if there's none
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(mut cmd) => {
+                cmd.redo();
+                self.done.push(cmd);
+                true
+            }
+            None => false
+        }
+    }
+
+    pub fn can_undo(&self) -> bool { !self.done.is_empty() }
+    pub fn can_redo(&self) -> bool { !self.undone.is_empty() }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    fn trim(&mut self) {
+        while self.done.len() > self.limit {
+            self.done.remove(0);
+        }
+    }
+}